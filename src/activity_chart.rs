@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use chrono::{Datelike, Utc};
+use druid::{BoxConstraints, Color, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Rect, RenderContext, Size, UpdateCtx, Widget};
+use crate::data::MonthActivity;
+use crate::ui_globals::ui;
+
+/// The fewest months the chart shows, so that a short activity doesn't get stretched into a few wide bars
+const MIN_CHART_MONTHS: i32 = 12;
+/// The most months the chart shows, so that the bars of a long activity stay wide enough to see
+const MAX_CHART_MONTHS: i32 = 120;
+const UPLOAD_COLOR: Color = Color::rgb8(120, 200, 255);
+const BOOKMARK_COLOR: Color = Color::rgb8(255, 200, 0);
+
+/// Bar chart of monthly uploads, with the bookmarked part of each month drawn over the upload bar
+pub struct ActivityChart {
+    width: f64,
+    height: f64,
+}
+
+impl ActivityChart {
+    pub fn new(width: u32, height: u32) -> ActivityChart {
+        ActivityChart {
+            width: width as f64,
+            height: height as f64,
+        }
+    }
+}
+
+/// Parses "YYYY-MM" into a month count since year 0. Returns None if the month is invalid
+fn parse_month(month: &str) -> Option<i32> {
+    let (year, month) = month.split_once('-')?;
+    let year = year.parse::<i32>().ok()?;
+    let month = month.parse::<i32>().ok()?;
+    if !(1..=12).contains(&month) {
+        return None;
+    }
+    Some(year * 12 + month - 1)
+}
+
+/// Returns the months from the oldest month of the activity to the current one as "YYYY-MM", oldest first. Always at least MIN_CHART_MONTHS and at most MAX_CHART_MONTHS months
+fn get_chart_months(activity: &[MonthActivity]) -> Vec<String> {
+    let now = Utc::now();
+    let current = now.year() * 12 + now.month0() as i32;
+    let oldest = activity.iter().filter_map(|activity| parse_month(&activity.month)).min().unwrap_or(current);
+    let chart_months = (current - oldest + 1).clamp(MIN_CHART_MONTHS, MAX_CHART_MONTHS);
+
+    (0..chart_months)
+        .rev()
+        .map(|offset| {
+            let month = current - offset;
+            format!("{:04}-{:02}", month.div_euclid(12), month.rem_euclid(12) + 1)
+        })
+        .collect()
+}
+
+impl Widget<Arc<Vec<MonthActivity>>> for ActivityChart {
+    fn event(&mut self, _: &mut EventCtx, _: &Event, _: &mut Arc<Vec<MonthActivity>>, _: &Env) {}
+
+    fn lifecycle(&mut self, _: &mut LifeCycleCtx, _: &LifeCycle, _: &Arc<Vec<MonthActivity>>, _: &Env) {}
+
+    fn update(&mut self, ctx: &mut UpdateCtx, old_data: &Arc<Vec<MonthActivity>>, data: &Arc<Vec<MonthActivity>>, _: &Env) {
+        if !Arc::ptr_eq(old_data, data) {
+            ctx.request_paint();
+        }
+    }
+
+    fn layout(&mut self, _: &mut LayoutCtx, bc: &BoxConstraints, _: &Arc<Vec<MonthActivity>>, _: &Env) -> Size {
+        bc.constrain(Size::new(self.width, self.height))
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &Arc<Vec<MonthActivity>>, _: &Env) {
+        let size = ctx.size();
+        ctx.fill(size.to_rect(), &ui::INACTIVE_COLOR);
+
+        let months: Vec<(u16, u16)> = get_chart_months(data)
+            .iter()
+            .map(|month| match data.iter().find(|activity| &activity.month == month) {
+                Some(activity) => (activity.uploads, activity.bookmarked),
+                None => (0, 0),
+            })
+            .collect();
+
+        let max_uploads = months.iter().map(|(uploads, _)| *uploads).max().unwrap_or(0);
+        if max_uploads == 0 {
+            return;
+        }
+
+        let padding = 4.0;
+        let bar_slot = (size.width - padding * 2.0) / months.len() as f64;
+        let max_bar_height = size.height - padding * 2.0;
+
+        for (index, (uploads, bookmarked)) in months.iter().enumerate() {
+            let x0 = padding + bar_slot * index as f64;
+            let x1 = x0 + (bar_slot - 1.0).max(1.0);
+            let bottom = size.height - padding;
+
+            let upload_height = max_bar_height * (*uploads as f64) / (max_uploads as f64);
+            ctx.fill(Rect::new(x0, bottom - upload_height, x1, bottom), &UPLOAD_COLOR);
+
+            let bookmark_height = max_bar_height * (*bookmarked as f64) / (max_uploads as f64);
+            ctx.fill(Rect::new(x0, bottom - bookmark_height, x1, bottom), &BOOKMARK_COLOR);
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::thread;
+use druid::{ExtEventSink, Selector, Target};
+use crate::data::UserData;
+use crate::pixiv_handler::{get_bookmarked_works, get_recent_works, set_bookmark};
+
+const BATCH_IDLE: u8 = 0;
+const BATCH_RUNNING: u8 = 1;
+const BATCH_CANCELLED: u8 = 2;
+
+/// State of the batch thread. BATCH_CANCELLED means that the thread is about to stop
+static BATCH_STATE: AtomicU8 = AtomicU8::new(BATCH_IDLE);
+
+/// Sent by the batch thread after every changed bookmark
+pub const BATCH_PROGRESS: Selector<BatchProgress> = Selector::new("par.batch-progress");
+/// Sent by the batch thread when it stops
+pub const BATCH_DONE: Selector<BatchResult> = Selector::new("par.batch-done");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Bookmark action for all of an artist's works at once
+pub enum BatchAction {
+    /// Bookmarks the 4 shown works
+    BookmarkShown,
+    /// Bookmarks all works from the activity window
+    BookmarkRecent,
+    /// Removes all of the user's bookmarks of the artist's works
+    RemoveAll,
+}
+
+impl BatchAction {
+    /// Whether the works should end up bookmarked
+    fn target_state(&self) -> bool {
+        *self != BatchAction::RemoveAll
+    }
+}
+
+pub struct BatchProgress {
+    pub artist_id: u32,
+    pub illust_id: u32,
+    pub target_state: bool,
+    /// Whether the bookmark ended up in the target state
+    pub success: bool,
+    pub done: usize,
+    pub total: usize,
+}
+
+pub struct BatchResult {
+    pub action: BatchAction,
+    pub artist_id: u32,
+    /// Ids of the works whose bookmarks were changed
+    pub changed: Vec<u32>,
+    pub failed: usize,
+    /// False if the batch was cancelled or pixiv could not be reached before all works were done
+    pub finished: bool,
+}
+
+pub fn is_batch_running() -> bool {
+    BATCH_STATE.load(Ordering::SeqCst) != BATCH_IDLE
+}
+
+/// Returns ids of the works that the action should change. Returns None if pixiv could not be reached
+fn get_batch_targets(data: &UserData, action: BatchAction, artist_id: u32, known_ids: Vec<u32>) -> Option<Vec<u32>> {
+    match action {
+        BatchAction::BookmarkShown => Some(known_ids),
+        BatchAction::BookmarkRecent => get_recent_works(data, artist_id, &data.activity_months),
+        BatchAction::RemoveAll => {
+            let mut illust_ids = get_bookmarked_works(data, artist_id)?;
+            for illust_id in known_ids {
+                if !illust_ids.contains(&illust_id) {
+                    illust_ids.push(illust_id);
+                }
+            }
+            Some(illust_ids)
+        },
+    }
+}
+
+fn run_batch(sink: ExtEventSink, data: UserData, action: BatchAction, artist_id: u32, known_ids: Vec<u32>) {
+    let target_state = action.target_state();
+    let mut result = BatchResult { action, artist_id, changed: Vec::new(), failed: 0, finished: false };
+
+    if let Some(illust_ids) = get_batch_targets(&data, action, artist_id, known_ids) {
+        result.finished = true;
+        let total = illust_ids.len();
+
+        for (index, illust_id) in illust_ids.into_iter().enumerate() {
+            if BATCH_STATE.load(Ordering::SeqCst) == BATCH_CANCELLED {
+                result.finished = false;
+                break;
+            }
+
+            let success = match set_bookmark(&data, illust_id, target_state) {
+                Some(success) => success,
+                None => {
+                    result.finished = false;
+                    break;
+                },
+            };
+
+            if success { result.changed.push(illust_id) }
+            else { result.failed += 1 }
+
+            let progress = BatchProgress { artist_id, illust_id, target_state, success, done: index + 1, total };
+            if sink.submit_command(BATCH_PROGRESS, progress, Target::Auto).is_err() {
+                // the app was closed
+                break;
+            }
+        }
+    }
+
+    BATCH_STATE.store(BATCH_IDLE, Ordering::SeqCst);
+    let _ = sink.submit_command(BATCH_DONE, result, Target::Auto);
+}
+
+/// Runs the action for the artist on a separate thread, sending BATCH_PROGRESS after every work and BATCH_DONE at the end. known_ids are the works to bookmark for BookmarkShown, and bookmarked works that are already known of for RemoveAll. Does nothing if a batch is already running
+pub fn start_batch(sink: ExtEventSink, data: UserData, action: BatchAction, artist_id: u32, known_ids: Vec<u32>) {
+    if BATCH_STATE.compare_exchange(BATCH_IDLE, BATCH_RUNNING, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        return;
+    }
+
+    thread::spawn(move || run_batch(sink, data, action, artist_id, known_ids));
+}
+
+/// Stops the batch after the work that is being changed right now. Works that were already changed stay changed
+pub fn cancel_batch() {
+    let _ = BATCH_STATE.compare_exchange(BATCH_RUNNING, BATCH_CANCELLED, Ordering::SeqCst, Ordering::SeqCst);
+}
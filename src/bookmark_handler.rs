@@ -0,0 +1,303 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use chrono::{DateTime, Utc};
+use druid::{ExtEventSink, Selector, Target};
+use serde::{Deserialize, Serialize};
+use crate::data::{ArtistResult, Illustration, UserData};
+use crate::date_handler::format_date;
+use crate::pixiv_handler::download_bookmarks;
+
+/// Bookmark comments by illustration id, only kept locally
+const BOOKMARK_COMMENTS_PATH: &str = "./bookmark_comments.json";
+/// All of the user's bookmarks
+const BOOKMARK_INDEX_PATH: &str = "./bookmarks.json";
+
+/// Sent by the bookmark sync thread with the amount of indexed bookmarks, None if pixiv could not be reached
+pub const BOOKMARK_SYNC_DONE: Selector<Option<usize>> = Selector::new("par.bookmark-sync-done");
+
+static BOOKMARK_SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Bookmarks of the index by artist id
+pub type ArtistBookmarks = HashMap<u32, Vec<IndexedBookmark>>;
+
+/// Cached bookmark index grouped by artist. Some(None) if the bookmarks were never synced
+static ARTIST_BOOKMARKS: Mutex<Option<Option<Arc<ArtistBookmarks>>>> = Mutex::new(None);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexedBookmark {
+    #[serde(flatten)]
+    pub illust: Illustration,
+    pub artist_id: u32,
+    /// Either "public" or "private"
+    pub restrict: String,
+    /// Whether the bookmark was made in PAR since the last sync
+    #[serde(default)]
+    pub is_local: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+/// Struct used for saving the bookmark index, newest bookmarks first
+pub struct BookmarkIndex {
+    pub synced_at: Option<DateTime<Utc>>,
+    pub bookmarks: Vec<IndexedBookmark>,
+}
+
+fn load_bookmark_comments() -> HashMap<u32, String> {
+    std::fs::read_to_string(BOOKMARK_COMMENTS_PATH).ok()
+        .and_then(|file_cont| serde_json::from_str(&file_cont).ok())
+        .unwrap_or_default()
+}
+
+fn save_bookmark_comments(comments: &HashMap<u32, String>) {
+    let file_cont = serde_json::to_string(comments)
+        .expect("Could not convert data to json");
+    std::fs::write(BOOKMARK_COMMENTS_PATH, file_cont)
+        .expect("Could not write to bookmark_comments.json");
+}
+
+/// Returns the local comment of the bookmark. Returns an empty string if there is none
+pub fn get_bookmark_comment(illust_id: u32) -> String {
+    load_bookmark_comments().remove(&illust_id).unwrap_or_default()
+}
+
+/// Saves the local comment of the bookmark. An empty comment removes it
+pub fn set_bookmark_comment(illust_id: u32, comment: &str) {
+    let mut comments = load_bookmark_comments();
+    let comment = comment.trim();
+
+    if comment.is_empty() {
+        if comments.remove(&illust_id).is_none() {
+            return;
+        }
+    } else {
+        comments.insert(illust_id, comment.to_string());
+    }
+
+    save_bookmark_comments(&comments);
+}
+
+/// Splits space separated bookmark tags
+pub fn parse_bookmark_tags(tags: &str) -> Vec<String> {
+    let mut parsed: Vec<String> = Vec::new();
+    for tag in tags.split_whitespace() {
+        if !parsed.iter().any(|t| t == tag) {
+            parsed.push(tag.to_string());
+        }
+    }
+    parsed
+}
+
+/// Adds the tag to space separated bookmark tags, unless it's already there
+pub fn add_bookmark_tag(tags: &str, tag: &str) -> String {
+    let mut parsed = parse_bookmark_tags(tags);
+    if !parsed.iter().any(|t| t == tag) {
+        parsed.push(tag.to_string());
+    }
+    parsed.join(" ")
+}
+
+/// Reads bookmarks.json. Returns None if the bookmarks were never synced
+pub fn load_bookmark_index() -> Option<BookmarkIndex> {
+    let file_cont = std::fs::read_to_string(BOOKMARK_INDEX_PATH).ok()?;
+    serde_json::from_str(&file_cont).ok()
+}
+
+/// Writes to a temporary file first, so that a half written index is never read
+fn save_bookmark_index(index: &BookmarkIndex) {
+    let temp_path = format!("{}.part", BOOKMARK_INDEX_PATH);
+    let file_cont = serde_json::to_string(index)
+        .expect("Could not convert data to json");
+    std::fs::write(&temp_path, file_cont)
+        .expect("Could not write to bookmarks.json");
+    std::fs::rename(&temp_path, BOOKMARK_INDEX_PATH)
+        .expect("Could not write to bookmarks.json");
+
+    *ARTIST_BOOKMARKS.lock().unwrap() = None;
+}
+
+fn group_by_artist(bookmarks: Vec<IndexedBookmark>) -> ArtistBookmarks {
+    let mut artist_bookmarks = ArtistBookmarks::new();
+    for bookmark in bookmarks {
+        artist_bookmarks.entry(bookmark.artist_id).or_default().push(bookmark);
+    }
+    artist_bookmarks
+}
+
+/// Returns the bookmark index grouped by artist. Returns None if the bookmarks were never synced
+pub fn get_artist_bookmarks() -> Option<Arc<ArtistBookmarks>> {
+    let mut cached = ARTIST_BOOKMARKS.lock().unwrap();
+    if cached.is_none() {
+        *cached = Some(load_bookmark_index().map(|index| Arc::new(group_by_artist(index.bookmarks))));
+    }
+    cached.clone().flatten()
+}
+
+/// Downloads the user's bookmarks into bookmarks.json, only the new ones unless full is true. Returns None if pixiv could not be reached
+fn sync_bookmark_index(data: &UserData, full: bool) -> Option<usize> {
+    let old_index = {
+        if full { BookmarkIndex::default() }
+        else { load_bookmark_index().unwrap_or_default() }
+    };
+
+    let mut new_bookmarks: Vec<IndexedBookmark> = Vec::new();
+    for restrict in ["public", "private"] {
+        let stop_at_id = old_index.bookmarks
+            .iter()
+            .find(|bookmark| bookmark.restrict == restrict && !bookmark.is_local)
+            .map(|bookmark| bookmark.illust.id)
+            .unwrap_or(0);
+        new_bookmarks.extend(download_bookmarks(data, restrict, stop_at_id)?);
+    }
+
+    // a bookmark that was removed and added again since the last sync shows up as a new one. Local bookmarks are newer than the last sync, so if they didn't get downloaded, they were removed in the meantime
+    let new_ids: HashSet<u32> = new_bookmarks.iter().map(|bookmark| bookmark.illust.id).collect();
+    new_bookmarks.extend(old_index.bookmarks.into_iter().filter(|bookmark| !bookmark.is_local && !new_ids.contains(&bookmark.illust.id)));
+
+    let index = BookmarkIndex {
+        synced_at: Some(Utc::now()),
+        bookmarks: new_bookmarks,
+    };
+    save_bookmark_index(&index);
+    Some(index.bookmarks.len())
+}
+
+pub fn is_bookmark_sync_running() -> bool {
+    BOOKMARK_SYNC_RUNNING.load(Ordering::SeqCst)
+}
+
+/// Syncs the bookmark index on a separate thread. Does nothing if a sync is already running
+pub fn start_bookmark_sync(sink: ExtEventSink, data: UserData, full: bool) {
+    if BOOKMARK_SYNC_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    thread::spawn(move || {
+        let result = sync_bookmark_index(&data, full);
+        BOOKMARK_SYNC_RUNNING.store(false, Ordering::SeqCst);
+        let _ = sink.submit_command(BOOKMARK_SYNC_DONE, result, Target::Auto);
+    });
+}
+
+/// Returns ids of the artist's works that are in the bookmark index
+pub fn get_indexed_bookmark_ids(artist_id: u32) -> Vec<u32> {
+    get_artist_bookmarks()
+        .and_then(|artist_bookmarks| artist_bookmarks.get(&artist_id).map(|bookmarks| bookmarks.iter().map(|bookmark| bookmark.illust.id).collect()))
+        .unwrap_or_default()
+}
+
+/// Returns the restrict of the user's bookmark of the illustration. Returns None if it is not in the index
+pub fn get_indexed_restrict(artist_id: u32, illust_id: u32) -> Option<String> {
+    let artist_bookmarks = get_artist_bookmarks()?;
+    artist_bookmarks.get(&artist_id)?.iter().find(|bookmark| bookmark.illust.id == illust_id).map(|bookmark| bookmark.restrict.clone())
+}
+
+/// Adds or removes a bookmark that was made in PAR, so that the index stays right until the next sync
+pub fn set_indexed_bookmark(artist_id: u32, illust: &Illustration, restrict: &str, is_bookmarked: bool) {
+    if is_bookmark_sync_running() {
+        return;
+    }
+
+    let mut index = match load_bookmark_index() {
+        Some(i) => i,
+        None => return,
+    };
+
+    let position = index.bookmarks.iter().position(|bookmark| bookmark.illust.id == illust.id);
+    match position {
+        Some(position) if !is_bookmarked => {
+            index.bookmarks.remove(position);
+        },
+        Some(position) if index.bookmarks[position].restrict != restrict => {
+            index.bookmarks[position].restrict = restrict.to_string();
+        },
+        None if is_bookmarked => {
+            let mut illust = illust.clone();
+            illust.is_bookmarked = true;
+            index.bookmarks.insert(0, IndexedBookmark { illust, artist_id, restrict: restrict.to_string(), is_local: true });
+        },
+        _ => return,
+    }
+
+    save_bookmark_index(&index);
+}
+
+/// Removes bookmarks that were deleted from pixiv from the bookmark index
+pub fn forget_indexed_bookmarks(illust_ids: &[u32]) {
+    if is_bookmark_sync_running() {
+        return;
+    }
+
+    if let Some(mut index) = load_bookmark_index() {
+        let count = index.bookmarks.len();
+        index.bookmarks.retain(|bookmark| !illust_ids.contains(&bookmark.illust.id));
+        if index.bookmarks.len() != count {
+            save_bookmark_index(&index);
+        }
+    }
+}
+
+/// Uses the artist's indexed bookmarks for the last bookmarked illustration. Returns the bookmark count and the ids of removed bookmarks
+pub fn apply_artist_bookmarks(artist_result: &mut ArtistResult, artist_bookmarks: &[IndexedBookmark]) -> (u32, Vec<u32>) {
+    let unbookmarked_ids = artist_result.unbookmarked_ids.as_deref().unwrap_or_default();
+    let (removed, artist_bookmarks): (Vec<&IndexedBookmark>, Vec<&IndexedBookmark>) = artist_bookmarks
+        .iter()
+        .partition(|bookmark| unbookmarked_ids.contains(&bookmark.illust.id));
+
+    let latest = artist_bookmarks.iter().max_by_key(|bookmark| bookmark.illust.upload_date);
+    if let Some(latest) = latest {
+        let is_more_recent = artist_result.last_bookmarked.id == 0 || latest.illust.upload_date > artist_result.last_bookmarked.upload_date;
+        if is_more_recent && latest.illust.upload_date.is_some() {
+            artist_result.last_bookmarked = latest.illust.clone();
+            // the works uploaded after it are only known within the searched range
+            artist_result.last_bookmarked_position = artist_result.upload_dates.as_ref()
+                .map(|upload_dates| upload_dates.iter().filter(|date| Some(**date) > latest.illust.upload_date).count() as u16);
+        }
+    }
+
+    (artist_bookmarks.len() as u32, removed.iter().map(|bookmark| bookmark.illust.id).collect())
+}
+
+/// Applies the artist's indexed bookmarks and forgets removed ones. Returns None if the bookmarks were never synced
+pub fn apply_bookmark_index(artist_result: &mut ArtistResult) -> Option<u32> {
+    let artist_bookmarks = get_artist_bookmarks()?;
+    let bookmarks = artist_bookmarks.get(&artist_result.artist.id).map(|bookmarks| bookmarks.as_slice()).unwrap_or_default();
+    let (count, removed) = apply_artist_bookmarks(artist_result, bookmarks);
+
+    if !removed.is_empty() {
+        forget_indexed_bookmarks(&removed);
+    }
+    Some(count)
+}
+
+/// Returns a short description of the bookmark index for the settings window
+pub fn describe_bookmark_index(index: &Option<BookmarkIndex>, data: &UserData) -> String {
+    match index {
+        Some(index) => format!("{} bookmarks, synced {}", index.bookmarks.len(), format_date(&index.synced_at, data, "never")),
+        None => "not synced".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_split_by_any_whitespace() {
+        assert_eq!(parse_bookmark_tags("  オリジナル  風景\tfanart\n"), vec!["オリジナル", "風景", "fanart"]);
+        assert!(parse_bookmark_tags("   ").is_empty());
+    }
+
+    #[test]
+    fn repeated_tags_are_kept_once() {
+        assert_eq!(parse_bookmark_tags("a b a c b"), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn added_tag_goes_last_unless_already_there() {
+        assert_eq!(add_bookmark_tag("a  b", "c"), "a b c");
+        assert_eq!(add_bookmark_tag("a b", "a"), "a b");
+        assert_eq!(add_bookmark_tag("", "a"), "a");
+    }
+}
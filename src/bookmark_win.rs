@@ -0,0 +1,186 @@
+use druid::commands::CLOSE_WINDOW;
+use druid::widget::{Label, Flex, Scroll, TextBox, Either, SizedBox};
+use druid::{WidgetExt, Widget, WindowDesc, EventCtx};
+use crate::bookmark_handler::add_bookmark_tag;
+use crate::data::AppData;
+use crate::ui_globals::ui::{self, create_button};
+
+fn create_titlebar() -> impl Widget<AppData> {
+    let bw = (ui::BOOKMARK_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.bookmark_illust.is_bookmarked { "Edit bookmark" }
+            else { "Bookmark" }
+        }),
+        bw, bh, fs, true)
+        .on_added(|_, ctx, _, _| {
+            unsafe { ui::bookmark_window_id = Some(ctx.window_id()) }
+        })
+}
+
+/// Creates a button for every tag of the illustration. Clicking on one adds it to the bookmark tags
+fn create_suggestion_container(data: &AppData) -> impl Widget<AppData> {
+    let bw = (ui::BOOKMARK_WINDOW_WIDTH as u32) - 10;
+    let bh = 30;
+    let fs = 20;
+
+    let mut cont = Flex::column();
+
+    let tags = &data.bookmark_illust.tags;
+    if tags.is_empty() {
+        cont.add_child(create_button(
+            Label::new("The illustration has no tags"),
+            bw, bh, fs, false));
+    }
+
+    for tag in tags.clone() {
+        cont.add_child(create_button(
+            Label::new(format!("+ {}", tag)),
+            bw, bh, fs, false)
+            .on_click(move |_, data: &mut AppData, _| {
+                data.bookmark_tags = add_bookmark_tag(&data.bookmark_tags, &tag);
+            }));
+    }
+
+    cont
+}
+
+fn create_footer() -> impl Widget<AppData> {
+    let bw = (ui::BOOKMARK_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    let label_status = Either::new(
+        |data: &AppData, _| data.bookmark_status.is_empty(),
+        SizedBox::empty(),
+        create_button(
+            Label::new(|data: &AppData, _: &_| data.bookmark_status.clone()),
+            bw, bh-10, fs-10, false),
+    );
+
+    let button_remove = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.bookmark_illust.is_bookmarked { "Remove" }
+            else { "" }
+        }),
+        bw/3, bh, fs, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.bookmark_illust.is_bookmarked {
+                data.remove_bookmark();
+                close_bookmark_window(ctx, data);
+            }
+        });
+    let button_cancel = create_button(
+        Label::new("Cancel"),
+        bw/3, bh, fs, true)
+        .on_click(|ctx, data, _| {
+            close_bookmark_window(ctx, data);
+        });
+    let button_save = create_button(
+        Label::new("Save"),
+        bw - (bw/3)*2, bh, fs, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.save_bookmark() { close_bookmark_window(ctx, data) }
+        });
+
+    Flex::column()
+        .with_child(label_status)
+        .with_child(
+            Flex::row()
+                .with_child(button_remove)
+                .with_child(button_cancel)
+                .with_child(button_save)
+        )
+}
+
+fn build_ui(data: &AppData) -> impl Widget<AppData> {
+    let bw = (ui::BOOKMARK_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    let titlebar = create_titlebar();
+
+    let label_tags = create_button(
+        Label::new("Bookmark tags:"),
+        bw, bh-10, fs-10, false);
+    let text_tags = TextBox::new()
+        .with_placeholder("separated by spaces")
+        .with_font(ui::FONT)
+        .with_text_size((fs-10) as f64)
+        .lens(AppData::bookmark_tags)
+        .fix_size(bw as f64, bh as f64);
+
+    let label_suggestions = create_button(
+        Label::new("Tags of the illustration:"),
+        bw, bh-10, fs-10, false);
+    let suggestion_container = create_suggestion_container(data);
+
+    let label_comment = create_button(
+        Label::new("Comment (only kept in PAR):"),
+        bw, bh-10, fs-10, false);
+    let text_comment = TextBox::new()
+        .with_font(ui::FONT)
+        .with_text_size((fs-10) as f64)
+        .lens(AppData::bookmark_comment)
+        .fix_size(bw as f64, bh as f64);
+
+    let button_visibility = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.bookmark_is_private { "Visibility: private" }
+            else { "Visibility: public" }
+        }),
+        bw, bh, fs-10, false)
+        .on_click(|_, data: &mut AppData, _| {
+            if data.bookmark_details_loaded { data.bookmark_is_private = !data.bookmark_is_private }
+        });
+
+    let footer = create_footer();
+
+    let scroll_height = ui::BOOKMARK_WINDOW_HEIGHT - 40.0 - 30.0 - 40.0 - 30.0 - 30.0 - 40.0 - 40.0 - 40.0 - 30.0 - 10.0 - 15.0;
+    let suggestions = Scroll::new(suggestion_container).vertical().fix_height(scroll_height);
+
+    Flex::column()
+        .with_child(titlebar)
+        .with_spacer(5.0)
+        .with_child(label_tags)
+        .with_child(text_tags)
+        .with_child(label_suggestions)
+        .with_child(suggestions)
+        .with_child(label_comment)
+        .with_child(text_comment)
+        .with_child(button_visibility)
+        .with_spacer(5.0)
+        .with_child(footer)
+        .padding(5.0)
+}
+
+fn create_bookmark_window(data: &AppData) -> WindowDesc<AppData> {
+    WindowDesc::new(build_ui(data))
+        .window_size((ui::BOOKMARK_WINDOW_WIDTH, ui::BOOKMARK_WINDOW_HEIGHT))
+        .set_position(unsafe { ui::BOOKMARK_WINDOW_POS })
+        .resizable(false)
+        .show_titlebar(false)
+        .title("PAR - Bookmark")
+}
+
+pub fn close_bookmark_window(ctx: &mut EventCtx, data: &mut AppData) {
+    if data.bookmark_window_is_open {
+        unsafe {
+            ctx.submit_command(CLOSE_WINDOW.to(ui::bookmark_window_id.unwrap()));
+            data.bookmark_window_is_open = false;
+            ui::bookmark_window_id = None;
+        }
+    }
+}
+
+/// Opens the bookmark window for the illustration. If the bookmark details can't be loaded, for example because the app is offline, the window shows the cached bookmark state, and only bookmarking without tags and removing the bookmark work
+pub fn open_bookmark_window(ctx: &mut EventCtx, data: &mut AppData, illust_index: usize) {
+    close_bookmark_window(ctx, data);
+    data.load_bookmark_details(illust_index);
+
+    ctx.new_window(create_bookmark_window(data));
+    data.bookmark_window_is_open = true;
+}
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{read_dir, remove_file, metadata};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use druid::{ExtEventSink, Selector, Target};
+use crate::data::UserData;
+use crate::ui_globals::ui;
+use crate::file_handler::{get_cached_artist_ids, load_artist_info, get_path_to_artist_json, get_path_to_pfp, get_path_to_illust, get_path_to_background};
+use crate::pixiv_handler::{redownload_image, ImageKind};
+
+const ACCESS_TIMES_PATH: &str = "./cache.json";
+
+/// How often (in seconds) enforce_cache_budget measures the size of the whole cache. Counting the cached artists is cheap, so that happens every time
+const BUDGET_CHECK_INTERVAL: u64 = 600;
+
+/// When the size of the whole cache was last measured, in unix seconds
+static LAST_BUDGET_CHECK: AtomicU64 = AtomicU64::new(0);
+
+/// Sent by the image download thread after every image that it downloaded
+pub const IMAGE_DOWNLOADED: Selector<(ImageKind, u32)> = Selector::new("par.image-downloaded");
+
+/// Images that are being downloaded in the background, so that they don't get requested twice
+static DOWNLOADING_IMAGES: Mutex<Vec<(ImageKind, u32)>> = Mutex::new(Vec::new());
+
+/// If max_cache_size is a valid u32 and is not 0, returns true, otherwise returns false
+pub fn check_cache_size_validity(max_cache_size: &str) -> bool {
+    matches!(max_cache_size.parse::<u32>(), Ok(num) if num > 0)
+}
+
+/// If max_cached_artists is a valid u16 and is at least 3 (the current artist and its neighbors), returns true, otherwise returns false
+pub fn check_cached_artists_validity(max_cached_artists: &str) -> bool {
+    matches!(max_cached_artists.parse::<u16>(), Ok(num) if num >= 3)
+}
+
+/// Returns the cache size budget in bytes
+pub fn get_max_cache_bytes(data: &UserData) -> u64 {
+    let megabytes = data.max_cache_size.parse::<u64>()
+        .unwrap_or_else(|_| ui::DEFAULT_MAX_CACHE_SIZE.parse().unwrap());
+    megabytes * 1024 * 1024
+}
+
+/// Returns the budget for the amount of cached artists
+pub fn get_max_cached_artists(data: &UserData) -> usize {
+    data.max_cached_artists.parse::<usize>()
+        .unwrap_or_else(|_| ui::DEFAULT_MAX_CACHED_ARTISTS.parse().unwrap())
+}
+
+/// Reads cache.json, which maps artist ids to the last time (in unix seconds) their cached info was accessed. Returns an empty map if the file is missing or invalid
+fn load_access_times() -> HashMap<u32, u64> {
+    std::fs::read_to_string(ACCESS_TIMES_PATH).ok()
+        .and_then(|file_cont| serde_json::from_str(&file_cont).ok())
+        .unwrap_or_default()
+}
+
+fn save_access_times(access_times: &HashMap<u32, u64>) {
+    let file_cont = serde_json::to_string(access_times)
+        .expect("Could not convert data to json");
+    std::fs::write(ACCESS_TIMES_PATH, file_cont)
+        .expect("Could not write to cache.json");
+}
+
+fn get_current_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn get_file_size(path: &str) -> u64 {
+    metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+/// Returns paths to the json and all images that belong to the cached artist
+fn get_artist_files(artist_id: u32) -> Vec<String> {
+    let mut files = vec![get_path_to_artist_json(artist_id), get_path_to_pfp(artist_id)];
+
+    if let Some(artist_info) = load_artist_info(artist_id) {
+        if artist_info.profile.as_ref().is_some_and(|profile| profile.has_background) {
+            files.push(get_path_to_background(artist_id));
+        }
+        let card_sets = artist_info.card_sets.iter().flat_map(|card_sets| card_sets.values()).flatten();
+        for illust in artist_info.illusts.iter().chain(card_sets) {
+            if illust.id != 0 && !files.contains(&get_path_to_illust(illust.id)) {
+                files.push(get_path_to_illust(illust.id));
+            }
+        }
+    }
+
+    files
+}
+
+/// Marks the artist's cached info as just accessed
+pub fn touch_artist(artist_id: u32) {
+    let mut access_times = load_access_times();
+    access_times.insert(artist_id, get_current_time());
+    save_access_times(&access_times);
+}
+
+/// Deletes all images that are not referenced by any cached artist json, as well as the json of the invalid artist with id 0 that pixiv sometimes returns. Returns the amount of removed files
+fn sweep_orphaned_files() -> usize {
+    let mut removed = 0;
+    let mut referenced = HashSet::new();
+
+    for artist_id in get_cached_artist_ids() {
+        if artist_id == 0 {
+            if remove_file(get_path_to_artist_json(0)).is_ok() {
+                removed += 1;
+            }
+            continue;
+        }
+
+        for path in get_artist_files(artist_id) {
+            referenced.insert(path);
+        }
+    }
+
+    let entries = match read_dir("./images") {
+        Ok(e) => e,
+        Err(_) => return removed,
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !file_name.ends_with(".jpeg") {
+            continue;
+        }
+
+        if !referenced.contains(&format!("./images/{}", file_name)) && remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+
+    removed
+}
+
+/// Keeps the cache within the byte and artist count budgets by deleting the least recently accessed artists first. Pinned artists never get deleted, even if that means going over the budget. Also sweeps orphaned files. Measuring the cache means reading every cached json, so it only happens when there are too many cached artists or when BUDGET_CHECK_INTERVAL has passed since the last time
+pub fn enforce_cache_budget(pinned: &[u32], max_bytes: u64, max_artists: usize) {
+    let now = get_current_time();
+    let is_check_due = now.saturating_sub(LAST_BUDGET_CHECK.load(Ordering::Relaxed)) >= BUDGET_CHECK_INTERVAL;
+    if !is_check_due && get_cached_artist_ids().len() <= max_artists {
+        return;
+    }
+    LAST_BUDGET_CHECK.store(now, Ordering::Relaxed);
+
+    sweep_orphaned_files();
+
+    let mut access_times = load_access_times();
+    let mut artists: Vec<(u32, u64, Vec<String>)> = get_cached_artist_ids()
+        .into_iter()
+        .map(|artist_id| (artist_id, *access_times.get(&artist_id).unwrap_or(&0), get_artist_files(artist_id)))
+        .collect();
+
+    let mut total_bytes: u64 = artists.iter()
+        .flat_map(|(_, _, files)| files.iter())
+        .map(|path| get_file_size(path))
+        .sum();
+    let mut total_artists = artists.len();
+
+    // least recently accessed first
+    artists.sort_by_key(|(_, access_time, _)| *access_time);
+
+    for (artist_id, _, files) in artists {
+        if total_bytes <= max_bytes && total_artists <= max_artists {
+            break;
+        }
+
+        if pinned.contains(&artist_id) {
+            continue;
+        }
+
+        for path in files {
+            total_bytes = total_bytes.saturating_sub(get_file_size(&path));
+            let _ = remove_file(path);
+        }
+
+        total_artists -= 1;
+        access_times.remove(&artist_id);
+    }
+
+    // forgetting artists whose files were deleted by something else
+    let cached_ids: HashSet<u32> = get_cached_artist_ids().into_iter().collect();
+    access_times.retain(|artist_id, _| cached_ids.contains(artist_id));
+    save_access_times(&access_times);
+}
+
+/// Downloads the missing or broken images on a separate thread, one at a time, and sends IMAGE_DOWNLOADED after each of them. Images that are already being downloaded get skipped
+pub fn start_image_download(sink: ExtEventSink, data: UserData, images: Vec<(ImageKind, u32)>) {
+    let images: Vec<(ImageKind, u32)> = {
+        let mut downloading = DOWNLOADING_IMAGES.lock().unwrap();
+        let images: Vec<(ImageKind, u32)> = images.into_iter().filter(|image| !downloading.contains(image)).collect();
+        downloading.extend(images.iter().copied());
+        images
+    };
+
+    if images.is_empty() {
+        return;
+    }
+
+    thread::spawn(move || {
+        for image in images {
+            let success = redownload_image(&data, image.0, image.1);
+            DOWNLOADING_IMAGES.lock().unwrap().retain(|downloading| *downloading != image);
+
+            if success && sink.submit_command(IMAGE_DOWNLOADED, image, Target::Auto).is_err() {
+                // the app was closed
+                break;
+            }
+        }
+    });
+}
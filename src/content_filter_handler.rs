@@ -0,0 +1,183 @@
+use druid::ImageBuf;
+use crate::data::{Illustration, UserData};
+
+/// Values of x_restrict_filter, in the order that the settings button cycles through them. "r18g" filters R-18G works, "r18" filters R-18 and R-18G works
+pub const X_RESTRICT_FILTERS: [&str; 3] = ["none", "r18g", "r18"];
+
+/// Blurred images get divided into this many blocks along their longer side
+const BLUR_BLOCKS: u32 = 12;
+
+/// If x_restrict_filter is one of X_RESTRICT_FILTERS, returns true, otherwise returns false
+pub fn check_x_restrict_filter_validity(x_restrict_filter: &str) -> bool {
+    X_RESTRICT_FILTERS.contains(&x_restrict_filter)
+}
+
+/// If ai_filter is either "show" or "filter", returns true, otherwise returns false
+pub fn check_ai_filter_validity(ai_filter: &str) -> bool {
+    ai_filter == "show" || ai_filter == "filter"
+}
+
+/// If filter_mode is either "blur" or "hide", returns true, otherwise returns false
+pub fn check_filter_mode_validity(filter_mode: &str) -> bool {
+    filter_mode == "blur" || filter_mode == "hide"
+}
+
+pub fn describe_x_restrict_filter(x_restrict_filter: &str) -> &'static str {
+    match x_restrict_filter {
+        "r18g" => "R-18G",
+        "r18" => "R-18 and R-18G",
+        _ => "nothing",
+    }
+}
+
+/// Returns true if any of the content filters can filter works
+fn has_active_filter(data: &UserData) -> bool {
+    data.x_restrict_filter != "none" || data.ai_filter == "filter" || !data.tag_blacklist.trim().is_empty()
+}
+
+/// Returns why the illustration should be filtered, like "R-18" or "tag: gore". Returns None if it passes all of the content filters
+pub fn get_filter_reason(illust: &Illustration, data: &UserData) -> Option<String> {
+    if illust.id == 0 {
+        return None;
+    }
+
+    // works from jsons saved by older versions of the app have no age restriction or ai flag, so they get filtered until they are downloaded again
+    let x_restrict = match illust.x_restrict {
+        Some(x_restrict) => x_restrict,
+        None if has_active_filter(data) => return Some("unknown rating".to_string()),
+        None => 0,
+    };
+    let max_x_restrict = match data.x_restrict_filter.as_str() {
+        "r18" => 0,
+        "r18g" => 1,
+        _ => 2,
+    };
+    if x_restrict > max_x_restrict {
+        return Some(if x_restrict == 2 { "R-18G" } else { "R-18" }.to_string());
+    }
+
+    if data.ai_filter == "filter" && illust.is_ai {
+        return Some("AI".to_string());
+    }
+
+    let blacklisted = data.tag_blacklist
+        .split_whitespace()
+        .find(|tag| illust.tags.iter().any(|illust_tag| illust_tag.eq_ignore_ascii_case(tag)));
+    blacklisted.map(|tag| format!("tag: {}", tag))
+}
+
+/// Returns a pixelated copy of the image, so that only its colors can be made out
+pub fn blur_image(image: &ImageBuf) -> ImageBuf {
+    let width = image.width();
+    let height = image.height();
+    let bytes_per_pixel = image.format().bytes_per_pixel();
+    let pixels = image.raw_pixels();
+
+    let block_size = ((width.max(height) as u32) / BLUR_BLOCKS).max(1) as usize;
+    let mut blurred = pixels.to_vec();
+
+    for block_y in (0..height).step_by(block_size) {
+        for block_x in (0..width).step_by(block_size) {
+            let rows = block_y..(block_y + block_size).min(height);
+            let columns = block_x..(block_x + block_size).min(width);
+            let pixel_count = rows.len() * columns.len();
+
+            let mut sums = vec![0usize; bytes_per_pixel];
+            for y in rows.clone() {
+                for x in columns.clone() {
+                    let offset = (y * width + x) * bytes_per_pixel;
+                    for (channel, sum) in sums.iter_mut().enumerate() {
+                        *sum += pixels[offset + channel] as usize;
+                    }
+                }
+            }
+
+            for y in rows.clone() {
+                for x in columns.clone() {
+                    let offset = (y * width + x) * bytes_per_pixel;
+                    for (channel, sum) in sums.iter().enumerate() {
+                        blurred[offset + channel] = (sum / pixel_count) as u8;
+                    }
+                }
+            }
+        }
+    }
+
+    ImageBuf::from_raw(blurred, image.format(), width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use druid::piet::ImageFormat;
+    use crate::test_fixtures::{make_illust, make_filter_settings};
+    use super::*;
+
+    #[test]
+    fn nothing_gets_filtered_without_filters() {
+        let settings = make_filter_settings("none", "show", "");
+        let illust = Illustration { x_restrict: Some(2), is_ai: true, tags: vec!["gore".to_string()], ..make_illust() };
+        assert_eq!(get_filter_reason(&illust, &settings), None);
+        assert_eq!(get_filter_reason(&Illustration { x_restrict: None, ..make_illust() }, &settings), None);
+    }
+
+    #[test]
+    fn age_restriction_filters() {
+        let r18g_filter = make_filter_settings("r18g", "show", "");
+        assert_eq!(get_filter_reason(&Illustration { x_restrict: Some(1), ..make_illust() }, &r18g_filter), None);
+        assert_eq!(get_filter_reason(&Illustration { x_restrict: Some(2), ..make_illust() }, &r18g_filter), Some("R-18G".to_string()));
+
+        let r18_filter = make_filter_settings("r18", "show", "");
+        assert_eq!(get_filter_reason(&make_illust(), &r18_filter), None);
+        assert_eq!(get_filter_reason(&Illustration { x_restrict: Some(1), ..make_illust() }, &r18_filter), Some("R-18".to_string()));
+        assert_eq!(get_filter_reason(&Illustration { x_restrict: Some(2), ..make_illust() }, &r18_filter), Some("R-18G".to_string()));
+    }
+
+    #[test]
+    fn unknown_rating_gets_filtered_by_any_filter() {
+        let illust = Illustration { x_restrict: None, ..make_illust() };
+        let reason = Some("unknown rating".to_string());
+        assert_eq!(get_filter_reason(&illust, &make_filter_settings("r18g", "show", "")), reason);
+        assert_eq!(get_filter_reason(&illust, &make_filter_settings("none", "filter", "")), reason);
+        assert_eq!(get_filter_reason(&illust, &make_filter_settings("none", "show", "gore")), reason);
+    }
+    #[test]
+    fn ai_and_tag_filters() {
+        let ai_illust = Illustration { is_ai: true, ..make_illust() };
+        assert_eq!(get_filter_reason(&ai_illust, &make_filter_settings("none", "filter", "")), Some("AI".to_string()));
+
+        let settings = make_filter_settings("none", "show", "gore  spoiler");
+        assert_eq!(get_filter_reason(&Illustration { tags: vec!["Spoiler".to_string()], ..make_illust() }, &settings), Some("tag: spoiler".to_string()));
+        assert_eq!(get_filter_reason(&Illustration { tags: vec!["landscape".to_string()], ..make_illust() }, &settings), None);
+    }
+
+    #[test]
+    fn empty_cards_never_get_filtered() {
+        let illust = Illustration { id: 0, x_restrict: None, is_ai: true, tags: vec!["gore".to_string()], ..make_illust() };
+        assert_eq!(get_filter_reason(&illust, &make_filter_settings("r18", "filter", "gore")), None);
+    }
+
+    #[test]
+    fn blur_averages_blocks() {
+        // 24 pixels wide makes blocks of 2 pixels, so the first two pixels become their average and the black rest stays black
+        let mut pixels = vec![0; 24 * 3];
+        pixels[..6].copy_from_slice(&[0, 100, 200, 100, 200, 0]);
+        let blurred = blur_image(&ImageBuf::from_raw(pixels, ImageFormat::Rgb, 24, 1));
+
+        assert_eq!(blurred.width(), 24);
+        assert_eq!(blurred.height(), 1);
+        assert_eq!(&blurred.raw_pixels()[..6], &[50, 150, 100, 50, 150, 100]);
+        assert!(blurred.raw_pixels()[6..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn blur_keeps_the_size_of_uneven_images() {
+        let width = 25;
+        let height = 13;
+        let pixels: Vec<u8> = (0..width * height * 4).map(|byte| byte as u8).collect();
+        let blurred = blur_image(&ImageBuf::from_raw(pixels, ImageFormat::RgbaSeparate, width, height));
+
+        assert_eq!(blurred.width(), width);
+        assert_eq!(blurred.height(), height);
+        assert_eq!(blurred.raw_pixels().len(), width * height * 4);
+    }
+}
@@ -1,32 +1,243 @@
+use std::collections::HashMap;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use druid::{Data, ImageBuf, Lens};
-use crate::file_handler::{get_image_buf, get_path_to_pfp, get_path_to_illust, get_small_artist_info, load_artist_info, get_last_checked_artist_index, mark_as_checked_up_to_index, does_path_exist, remove_old_artists};
-use crate::pixiv_handler::{download_artist_info, check_token_validity, check_amount_to_search_validity, check_timezone_validity, reset_artist_list};
+use chrono::{DateTime, Utc};
+use druid::{Data, ExtEventSink, ImageBuf, Lens};
+use crate::file_handler::{get_image_buf, get_path_to_pfp, get_path_to_illust, get_path_to_background, get_small_artist_info, load_artist_info, get_last_checked_artist_index, mark_as_checked_before_index, can_undo_mark_as_checked, undo_mark_as_checked, get_cached_artist_ids, find_cached_artist_index, remove_broken_cache_files, find_missing_images, set_cached_bookmark, set_cached_novel_bookmark, set_cached_follow, update_artist_entry, get_artist_entries, find_artist_index, add_artist_entry};
+use crate::rule_handler::{evaluate_rules, reevaluate_all_rules, get_rules_error, RuleMatches};
+use crate::scan_handler::{ScanUpdate, ScanProgress, start_scan, pause_scan, cancel_scan, mark_scan_stopped, is_scan_active, is_scan_paused, can_resume_scan, load_scan_progress, save_scan_progress, remove_scan_progress};
+use crate::queue_handler::{get_review_queue, find_next_in_queue, get_next_option, get_queue_filter_note, QUEUE_SORTS, QUEUE_FILTERS};
+use crate::bookmark_handler::{get_bookmark_comment, set_bookmark_comment, parse_bookmark_tags, load_bookmark_index, apply_bookmark_index, get_indexed_bookmark_ids, set_indexed_bookmark, start_bookmark_sync, is_bookmark_sync_running, describe_bookmark_index};
+use crate::batch_handler::{BatchAction, BatchProgress, BatchResult, start_batch, cancel_batch, is_batch_running};
+use crate::unfollow_handler::{record_unfollow, UnfollowRecord};
+use crate::content_filter_handler::{get_filter_reason, blur_image, check_x_restrict_filter_validity, check_ai_filter_validity, check_filter_mode_validity};
+use crate::score_handler::{calculate_affinity, check_score_weights_validity};
+use crate::outbox_handler::{describe_outbox, flush_outbox, clear_outbox, OutboxAction};
+use crate::cache_handler::{touch_artist, enforce_cache_budget, check_cache_size_validity, check_cached_artists_validity, get_max_cache_bytes, get_max_cached_artists};
+use crate::pixiv_handler::{download_artist_info, check_token_validity, check_amount_to_search_validity, check_activity_months_validity, reset_artist_list, toggle_follow, parse_lookup_input, get_illust_artist_id, set_follow_visibility, mute_artist, check_follow_list_validity, check_activity_works_validity, get_bookmark_detail, save_bookmark, change_bookmark, get_tagged_works, ImageKind, LookupTarget, CARD_MODES};
+use crate::date_handler::{deserialize_date, check_timezone_validity, check_date_format_validity};
 use crate::ui_globals::ui;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Data)]
 pub struct UserData {
     /// pixiv's refresh token
     pub token: String,
-    /// Max amount of illustrations to search for last bookmarked illustration and for recent illustration count. min=0, max=1020. It is more optimal for it to be divisible by 30
+    /// Max amount of illustrations to search for last bookmarked illustration, recent illustration count and monthly activity. min=0, max=65535. It is more optimal for it to be divisible by 30
     pub amount_to_search: String,
-    /// All dates will get converted to this timezone. Set to "" to use pixiv's timezone. For formatting check python's pytz module
+    /// All dates will get converted to this timezone. Set to "" to use pixiv's timezone. Has to be an IANA timezone name, like "Europe/Berlin"
     pub timezone: String,
+    /// Illustrations uploaded in this many last months count as recent. min=1, max=120
+    #[serde(default = "default_activity_months")]
+    pub activity_months: String,
+    /// How much recency, frequency and recent works affect the affinity score, formatted as "recency,frequency,recent works"
+    #[serde(default = "default_score_weights")]
+    pub score_weights: String,
+    /// Either "absolute" for dates like "2023/05/01 12:00:00" or "relative" for dates like "3 days ago"
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+    /// Max size of the downloaded artist jsons and images in megabytes. The least recently viewed artists get deleted first
+    #[serde(default = "default_max_cache_size")]
+    pub max_cache_size: String,
+    /// Max amount of artists whose jsons and images are kept on disk
+    #[serde(default = "default_max_cached_artists")]
+    pub max_cached_artists: String,
+    /// Either "public" to only download public follows into the artist list, or "all" to also download private follows
+    #[serde(default = "default_follow_list")]
+    pub follow_list: String,
+    /// Either "all" to count novels towards recent works and activity, or "illusts" to only count illustrations
+    #[serde(default = "default_activity_works")]
+    pub activity_works: String,
+    /// Which works get filtered by their age restriction. One of content_filter_handler::X_RESTRICT_FILTERS
+    #[serde(default = "default_x_restrict_filter")]
+    pub x_restrict_filter: String,
+    /// Either "show" or "filter" for AI-generated works
+    #[serde(default = "default_ai_filter")]
+    pub ai_filter: String,
+    /// Works with any of these tags get filtered. Separated by spaces
+    #[serde(default)]
+    pub tag_blacklist: String,
+    /// Either "blur" to blur filtered works until they get clicked on, or "hide" to not show them at all
+    #[serde(default = "default_filter_mode")]
+    pub filter_mode: String,
+    /// Which works fill the 4 cards. One of pixiv_handler::CARD_MODES
+    #[serde(default = "default_card_mode")]
+    pub card_mode: String,
+    /// Order in which < and > walk through the artists. One of queue_handler::QUEUE_SORTS
+    #[serde(default = "default_queue_sort")]
+    pub queue_sort: String,
+    /// Which artists < and > walk through. One of queue_handler::QUEUE_FILTERS
+    #[serde(default = "default_queue_filter")]
+    pub queue_filter: String,
+    /// Whether the unfollow window has "also mute" checked, remembered from the last unfollow
+    #[serde(default)]
+    pub unfollow_mute: bool,
+    /// Whether the unfollow window has "remove my bookmarks" checked, remembered from the last unfollow
+    #[serde(default)]
+    pub unfollow_remove_bookmarks: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-/// Struct used for retrieving artist info from json files
+fn default_activity_months() -> String {
+    ui::DEFAULT_ACTIVITY_MONTHS.to_string()
+}
+
+fn default_recent_months() -> u16 {
+    6
+}
+
+fn default_score_weights() -> String {
+    ui::DEFAULT_SCORE_WEIGHTS.to_string()
+}
+
+fn default_date_format() -> String {
+    ui::DEFAULT_DATE_FORMAT.to_string()
+}
+
+fn default_max_cache_size() -> String {
+    ui::DEFAULT_MAX_CACHE_SIZE.to_string()
+}
+
+fn default_max_cached_artists() -> String {
+    ui::DEFAULT_MAX_CACHED_ARTISTS.to_string()
+}
+
+fn default_follow_list() -> String {
+    ui::DEFAULT_FOLLOW_LIST.to_string()
+}
+
+fn default_activity_works() -> String {
+    ui::DEFAULT_ACTIVITY_WORKS.to_string()
+}
+
+fn default_x_restrict_filter() -> String {
+    ui::DEFAULT_X_RESTRICT_FILTER.to_string()
+}
+
+fn default_ai_filter() -> String {
+    ui::DEFAULT_AI_FILTER.to_string()
+}
+
+fn default_filter_mode() -> String {
+    ui::DEFAULT_FILTER_MODE.to_string()
+}
+
+fn default_card_mode() -> String {
+    ui::DEFAULT_CARD_MODE.to_string()
+}
+
+fn default_visibility() -> String {
+    "public".to_string()
+}
+
+fn default_queue_sort() -> String {
+    ui::DEFAULT_QUEUE_SORT.to_string()
+}
+
+fn default_queue_filter() -> String {
+    ui::DEFAULT_QUEUE_FILTER.to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Struct used for retrieving artist info from json files. The Option fields are None if the json was saved by an older version of the app
 pub struct ArtistResult {
     pub artist: Artist,
     pub last_bookmarked: Illustration,
+    /// How many newer illustrations the artist uploaded after the latest bookmarked one. None if there is no bookmarked illustration
+    #[serde(default)]
+    pub last_bookmarked_position: Option<u16>,
     pub illusts: [Illustration; 4],
+    /// Cards of the other card modes, by card mode. The most recent works are always in illusts, since the summary is based on them
+    #[serde(default)]
+    pub card_sets: Option<HashMap<String, [Illustration; 4]>>,
+    /// Up to 4 most recent novels
+    #[serde(default)]
+    pub novels: Option<Vec<Novel>>,
+    /// The artist's most used tags over the searched range, most used first
+    #[serde(default)]
+    pub top_tags: Option<Vec<TopTag>>,
+    /// Upload dates of all searched illustrations, most recent first
+    #[serde(default)]
+    pub upload_dates: Option<Vec<DateTime<Utc>>>,
+    /// Ids of the searched illustrations that the user hasn't bookmarked
+    #[serde(default)]
+    pub unbookmarked_ids: Option<Vec<u32>>,
+    /// Monthly upload counts over the searched range
+    #[serde(default)]
+    pub activity: Option<Vec<MonthActivity>>,
+    /// Details from the artist's profile. Also None if the json was saved by a scan
+    #[serde(default)]
+    pub profile: Option<ArtistProfile>,
+    /// When the info was downloaded from pixiv
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub fetched_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Struct used for storing followed artists in artists.json
+pub struct ArtistEntry {
+    pub id: u32,
+    /// Whether the artist has been reviewed
+    pub is_checked: bool,
+    /// Summary of the artist's info from the last time it was downloaded. None if it was never downloaded
+    #[serde(default)]
+    pub summary: Option<ArtistSummary>,
+    /// Results of the review rules from the last time the summary changed
+    #[serde(default)]
+    pub rule_matches: RuleMatches,
+    /// Whether the artist is followed publicly ("public") or privately ("private")
+    #[serde(default = "default_visibility")]
+    pub visibility: String,
+    /// When the artist was last opened in the main window. Marking artists as reviewed in bulk doesn't change it
+    #[serde(default)]
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Everything about an artist that is needed for scoring and sorting, without having to keep the artist's json and images around
+pub struct ArtistSummary {
+    pub name: String,
+    pub recent_count: u16,
+    pub recent_months: u16,
+    pub is_followed: bool,
+    /// Upload date of the latest bookmarked illustration. None if there are no bookmarks in the searched range
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub last_bookmarked: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_date")]
+    pub latest_upload: Option<DateTime<Utc>>,
+    /// How many illustrations were uploaded after the latest bookmarked one. If there are no bookmarks in the searched range, this is the size of the searched range
+    #[serde(default)]
+    pub works_since_last_bookmark: Option<u16>,
+    /// Amount of illustrations in the searched range
+    pub searched_count: u16,
+    /// Amount of bookmarked illustrations in the searched range
+    pub bookmarked_count: u16,
+    /// Amount of the most recent illustrations shown on the main window
+    pub shown_count: u8,
+    /// Amount of the shown illustrations that are bookmarked
+    pub shown_bookmarked: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Amount of illustrations that were uploaded in a single month
+pub struct MonthActivity {
+    /// Formatted as "YYYY-MM"
+    pub month: String,
+    pub uploads: u16,
+    /// How many of the uploaded illustrations are bookmarked by the user
+    pub bookmarked: u16,
 }
 
 #[derive(Debug, Clone, Data, Lens)]
 pub struct AppData {
     pub requires_initialization: bool,
+    /// Whether the app only browses the artists that are cached on disk, without using pixiv. Gets enabled automatically if pixiv can't be reached
+    pub is_offline: bool,
+    /// Whether the user switched to the offline mode, in which case the app doesn't go back online by itself
+    pub is_offline_by_choice: bool,
     pub window_title: String,
     pub settings_window_is_open: bool,
+    pub ranking_window_is_open: bool,
+    pub list_window_is_open: bool,
+    pub bookmark_window_is_open: bool,
     pub info_window_is_open: bool,
     /// Artist index to jump to when using jump to artist
     pub jump_index: String,
@@ -36,30 +247,149 @@ pub struct AppData {
     pub temp_token: String,
     pub temp_amount_to_search: String,
     pub temp_timezone: String,
-    /// Maximum amount of artist jsons and images to store
-    pub max_downloaded_artists: usize,
+    pub temp_activity_months: String,
+    pub temp_score_weights: String,
+    pub temp_date_format: String,
+    pub temp_max_cache_size: String,
+    pub temp_max_cached_artists: String,
+    pub temp_follow_list: String,
+    pub temp_activity_works: String,
+    pub temp_x_restrict_filter: String,
+    pub temp_ai_filter: String,
+    pub temp_tag_blacklist: String,
+    pub temp_filter_mode: String,
+    /// Result of the last cache verification, shown in the settings window
+    pub cache_report: String,
+    /// Result of the last bulk rule evaluation, shown in the settings window
+    pub rules_report: String,
+    /// True if the current artist was looked up and is not in artists.json
+    pub is_looked_up: bool,
+    /// Artist id or pixiv url to look up
+    pub lookup_input: String,
+    /// Why the last lookup failed. Empty if it didn't
+    pub lookup_status: String,
+    /// Illustration that the bookmark window is editing. It's a copy, so that changing the artist or the cards doesn't change which illustration gets bookmarked
+    pub bookmark_illust: Illustration,
+    /// Id of the artist who uploaded bookmark_illust
+    pub bookmark_artist_id: u32,
+    /// Whether the bookmark tags and visibility were loaded from pixiv. If they weren't, saving only bookmarks the illustration without tags
+    pub bookmark_details_loaded: bool,
+    /// Bookmark tags in the bookmark window, separated by spaces
+    pub bookmark_tags: String,
+    /// Local comment in the bookmark window
+    pub bookmark_comment: String,
+    pub bookmark_is_private: bool,
+    /// Why the last bookmark change failed. Empty if it didn't
+    pub bookmark_status: String,
+    /// Whether the last bulk review marking can be undone
+    pub can_undo_review_marks: bool,
+    // Options of the unfollow window
+    pub unfollow_mute: bool,
+    pub unfollow_remove_bookmarks: bool,
+    /// Why the artist is being unfollowed, only kept in unfollowed.json
+    pub unfollow_reason: String,
+    /// Name search of the artist list window
+    pub list_search: String,
+    /// Column that the artist list window is sorted by. Empty means the order of artists.json
+    pub list_sort: String,
+    pub list_sort_descending: bool,
+    /// Size and age of the local bookmark index
+    pub bookmark_index_report: String,
+    /// Progress or result of the last bulk bookmark action
+    pub batch_status: String,
+    /// Progress of the bulk scan of the whole artist list
+    pub scan_status: String,
+    /// Bookmarks and follows that could not be sent to pixiv yet, one per line
+    pub pending_changes: String,
     /// Whether the artist has been checked or not (aka whether their info was downloaded for the first time or not)
     pub has_been_checked: bool,
+    /// When the current artist was reviewed before this time. None if they never were
+    #[data(eq)]
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+    /// How many of the searched works were uploaded after last_reviewed_at
+    pub new_works_count: usize,
     /// Total artist count
     pub total_artists: u16,
     /// Current artist index
     pub artist_index: u16,
+    /// Artist indexes that < and > walk through, in order. Built from queue_sort and queue_filter when the walk starts
+    pub review_queue: Arc<Vec<u16>>,
+    /// What has to be done for queue_filter to find anyone, for example a scan for the new works filter. Empty if nothing
+    pub queue_filter_note: String,
     pub pfp_image: Option<ImageBuf>,
+    pub background_image: Option<ImageBuf>,
     pub illust_images: [Option<ImageBuf>; 4],
+    /// Images that were missing or broken when they were shown. The delegate hands them to the background download and empties the list
+    pub missing_images: Arc<Vec<(ImageKind, u32)>>,
+    /// Why each of the shown illustrations got blurred or hidden by the content filters. Empty if it didn't
+    pub illust_filters: [String; 4],
     pub artist: Artist,
+    /// Profile details of the current artist. None if they were never downloaded
+    pub profile: Option<ArtistProfile>,
+    /// Whether the profile panel is shown in place of the illustrations
+    pub profile_is_expanded: bool,
+    /// Whether the current artist is followed publicly ("public") or privately ("private"). Also used when following them
+    pub follow_visibility: String,
     /// Latest bookmarked illustration
     pub last_bookmarked: Illustration,
+    /// How many of the current artist's works the user has bookmarked. None if the bookmarks were never synced
+    pub artist_bookmark_count: Option<u32>,
     /// 4 most recent illustrations
     pub illusts: [Illustration; 4],
+    /// Up to 4 most recent novels
+    #[data(eq)]
+    pub novels: Vec<Novel>,
+    /// Whether the novels are shown in place of the illustrations
+    pub shows_novels: bool,
+    /// The current artist's most used tags, most used first
+    pub top_tags: Arc<Vec<TopTag>>,
+    /// Tag that the shown illustrations are filtered to. Empty if they are not filtered
+    pub tag_filter: String,
+    /// How engaged the user is with the current artist, from 0 to 100
+    pub affinity: f64,
+    /// Names of the review rules that the current artist matched, separated by commas
+    pub matched_rules: String,
+    /// Monthly upload counts over the searched range, oldest month first
+    pub activity: Arc<Vec<MonthActivity>>,
+    /// When the current artist's info was downloaded from pixiv
+    #[data(eq)]
+    pub fetched_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Data)]
 pub struct Artist {
     pub name: String,
     pub id: u32,
-    /// Illustrations uploaded in the last 6 months
+    /// Illustrations uploaded in the last recent_months months
     pub recent_count: u16,
+    /// Activity window that recent_count was counted for. Older versions of the app always used 6 months
+    #[serde(default = "default_recent_months")]
+    pub recent_months: u16,
     pub is_followed: bool,
+    /// Novels uploaded in the last recent_months months. Included in recent_count if counts_novels is true
+    #[serde(default)]
+    pub recent_novel_count: u16,
+    /// Whether novels were counted towards recent_count and activity
+    #[serde(default)]
+    pub counts_novels: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Data)]
+/// Details from the artist's pixiv profile
+pub struct ArtistProfile {
+    /// The artist's bio
+    pub comment: String,
+    pub webpage: String,
+    /// Urls of the artist's accounts on other sites
+    #[data(eq)]
+    pub social_links: Vec<String>,
+    pub total_illusts: u32,
+    pub total_manga: u32,
+    pub total_novels: u32,
+    pub is_premium: bool,
+    pub region: String,
+    /// Whether the artist has set a profile background image
+    pub has_background: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Data)]
@@ -67,8 +397,130 @@ pub struct Illustration {
     pub id: u32,
     pub views: u32,
     pub bookmarks: u32,
-    pub upload_date: String,
+    #[serde(deserialize_with = "deserialize_date")]
+    #[data(eq)]
+    pub upload_date: Option<DateTime<Utc>>,
     pub is_bookmarked: bool,
+    /// Tags of the illustration, suggested when bookmarking it
+    #[serde(default)]
+    #[data(eq)]
+    pub tags: Vec<String>,
+    /// 0 for all ages, 1 for R-18 and 2 for R-18G. None if the json was saved by an older version of the app
+    #[serde(default)]
+    pub x_restrict: Option<u8>,
+    /// Whether pixiv marks the illustration as AI-generated. Only meaningful if x_restrict is not None
+    #[serde(default)]
+    pub is_ai: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Data, PartialEq)]
+pub struct TopTag {
+    pub name: String,
+    /// English name of the tag. None if pixiv has no translation for it
+    pub translated_name: Option<String>,
+    /// How many of the searched works have the tag
+    pub count: u16,
+}
+
+impl TopTag {
+    /// Returns the translated name if there is one, otherwise the original name
+    pub fn display_name(&self) -> &str {
+        self.translated_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Data, PartialEq)]
+pub struct Novel {
+    pub id: u32,
+    pub title: String,
+    /// Length of the novel in characters
+    pub text_length: u32,
+    pub views: u32,
+    pub bookmarks: u32,
+    #[serde(deserialize_with = "deserialize_date")]
+    #[data(eq)]
+    pub upload_date: Option<DateTime<Utc>>,
+    pub is_bookmarked: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Struct used for retrieving the user's bookmark of an illustration
+pub struct BookmarkDetail {
+    pub is_bookmarked: bool,
+    /// Either "public" or "private"
+    pub restrict: String,
+    /// Bookmark tags that the user gave to the illustration
+    pub tags: Vec<String>,
+}
+
+impl ArtistResult {
+    /// Returns true if the json was saved by an older version of the app and is missing information that the current version needs, or if it was downloaded with different settings
+    pub fn is_outdated(&self, data: &UserData) -> bool {
+        self.fetched_at.is_none()
+            || self.activity.is_none()
+            || self.profile.is_none()
+            || self.novels.is_none()
+            || self.top_tags.is_none()
+            || self.card_sets.is_none()
+            || self.upload_dates.is_none()
+            || self.artist.counts_novels != (data.activity_works == "all")
+            || self.illusts.iter().any(|illust| illust.id != 0 && (illust.upload_date.is_none() || illust.x_restrict.is_none()))
+            || data.activity_months.trim().parse::<u16>().ok() != Some(self.artist.recent_months)
+    }
+
+    /// Returns the cards of the card mode. Unknown card modes get the most recent works
+    pub fn get_cards(&self, card_mode: &str) -> [Illustration; 4] {
+        self.card_sets.as_ref()
+            .and_then(|card_sets| card_sets.get(card_mode))
+            .unwrap_or(&self.illusts)
+            .clone()
+    }
+}
+
+impl ArtistEntry {
+    pub fn new(id: u32, is_checked: bool) -> ArtistEntry {
+        ArtistEntry {
+            id,
+            is_checked,
+            summary: None,
+            rule_matches: RuleMatches::default(),
+            visibility: default_visibility(),
+            reviewed_at: None,
+        }
+    }
+}
+
+impl ArtistSummary {
+    pub fn from_result(artist_result: &ArtistResult) -> ArtistSummary {
+        let activity = artist_result.activity.as_deref().unwrap_or_default();
+        let shown: Vec<&Illustration> = artist_result.illusts.iter().filter(|illust| illust.id != 0).collect();
+        let searched_count = activity.iter().map(|month| month.uploads).sum();
+
+        ArtistSummary {
+            name: artist_result.artist.name.clone(),
+            recent_count: artist_result.artist.recent_count,
+            recent_months: artist_result.artist.recent_months,
+            is_followed: artist_result.artist.is_followed,
+            last_bookmarked: {
+                if artist_result.last_bookmarked.id == 0 { None }
+                else { artist_result.last_bookmarked.upload_date }
+            },
+            latest_upload: {
+                let latest_illust = shown.first().and_then(|illust| illust.upload_date);
+                let latest_novel = artist_result.novels.as_ref().and_then(|novels| novels.first()).and_then(|novel| novel.upload_date);
+                if artist_result.artist.counts_novels { latest_illust.max(latest_novel) }
+                else { latest_illust }
+            },
+            works_since_last_bookmark: {
+                if artist_result.last_bookmarked.id == 0 { Some(searched_count) }
+                else { artist_result.last_bookmarked_position }
+            },
+            searched_count,
+            bookmarked_count: activity.iter().map(|month| month.bookmarked).sum(),
+            shown_count: shown.len() as u8,
+            shown_bookmarked: shown.iter().filter(|illust| illust.is_bookmarked).count() as u8,
+        }
+    }
 }
 
 impl UserData {
@@ -91,10 +543,47 @@ impl UserData {
             token: "None".to_string(),
             amount_to_search: ui::DEFAULT_AMOUNT_TO_SEARCH.to_string(),
             timezone: ui::DEFAULT_TIMEZONE.to_string(),
+            activity_months: default_activity_months(),
+            score_weights: default_score_weights(),
+            date_format: default_date_format(),
+            max_cache_size: default_max_cache_size(),
+            max_cached_artists: default_max_cached_artists(),
+            follow_list: default_follow_list(),
+            activity_works: default_activity_works(),
+            x_restrict_filter: default_x_restrict_filter(),
+            ai_filter: default_ai_filter(),
+            tag_blacklist: String::new(),
+            filter_mode: default_filter_mode(),
+            card_mode: default_card_mode(),
+            queue_sort: default_queue_sort(),
+            queue_filter: default_queue_filter(),
+            unfollow_mute: false,
+            unfollow_remove_bookmarks: false,
         }
     }
 }
 
+/// Applies the temp value of a setting if it is valid, resets the setting to default if the temp value is empty, or reverts the temp value otherwise. Returns true if the setting has changed
+fn apply_setting(setting: &mut String, temp: &mut String, default: &str, is_valid: fn(&str) -> bool) -> bool {
+    *temp = temp.trim().to_string();
+
+    if *setting == *temp {
+        return false;
+    }
+
+    if temp.is_empty() {
+        *temp = default.to_string();
+        *setting = default.to_string();
+        true
+    } else if is_valid(temp) {
+        *setting = temp.clone();
+        true
+    } else {
+        *temp = setting.clone();
+        false
+    }
+}
+
 fn make_window_title(artist_name: &str, artist_index: u16, total_artists: u16) -> String {
     format!("PAR - {} - {}/{}", artist_name, artist_index+1, total_artists)
 }
@@ -104,82 +593,770 @@ impl AppData {
         self.window_title = make_window_title(&self.artist.name, self.artist_index, self.total_artists);
     }
 
+    /// Returns true if the app is allowed to talk to pixiv
+    pub fn can_use_pixiv(&self) -> bool {
+        !self.requires_initialization && !self.is_offline
+    }
+
+    /// Loads an image from disk. Returns None if the image is missing or broken, in which case the placeholder gets shown and the image gets queued for a background download
+    fn load_image(&mut self, kind: ImageKind, id: u32) -> Option<ImageBuf> {
+        let path = match kind {
+            ImageKind::Pfp => get_path_to_pfp(id),
+            ImageKind::Illust => get_path_to_illust(id),
+            ImageKind::Background => get_path_to_background(id),
+        };
+
+        let image_buf = get_image_buf(&path);
+        if image_buf.is_none() && self.can_use_pixiv() && !self.missing_images.contains(&(kind, id)) {
+            Arc::make_mut(&mut self.missing_images).push((kind, id));
+        }
+        image_buf
+    }
+
+    /// Returns the images queued by load_image and empties the queue
+    pub fn take_missing_images(&mut self) -> Vec<(ImageKind, u32)> {
+        std::mem::take(Arc::make_mut(&mut self.missing_images))
+    }
+
+    /// Shows an image that finished downloading in the background, if it still belongs to the shown artist
+    pub fn show_downloaded_image(&mut self, kind: ImageKind, id: u32) {
+        match kind {
+            ImageKind::Pfp if id == self.artist.id => self.pfp_image = self.load_image(kind, id),
+            ImageKind::Background if id == self.artist.id => self.background_image = self.load_image(kind, id),
+            ImageKind::Illust => {
+                for index in 0..4 {
+                    if self.illusts[index].id == id {
+                        self.load_illust_image(index);
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Loads the illustration at index, blurring or hiding it if the content filters say so
+    fn load_illust_image(&mut self, index: usize) {
+        let filter_reason = get_filter_reason(&self.illusts[index], &self.settings);
+
+        if self.illusts[index].id == 0 || (filter_reason.is_some() && self.settings.filter_mode == "hide") {
+            self.illust_images[index] = None;
+        } else {
+            let image = self.load_image(ImageKind::Illust, self.illusts[index].id);
+            self.illust_images[index] = {
+                if filter_reason.is_some() { image.map(|image| blur_image(&image)) }
+                else { image }
+            };
+        }
+
+        self.illust_filters[index] = filter_reason.unwrap_or_default();
+    }
+
     pub fn load_images(&mut self) {
         if self.artist.id != 0 {
-            let image_buf_pfp = get_image_buf(&get_path_to_pfp(self.artist.id));
-            self.pfp_image = Some(image_buf_pfp);
+            self.pfp_image = self.load_image(ImageKind::Pfp, self.artist.id);
         } else {
             self.pfp_image = None;
         }
-        
+
+        let has_background = self.profile.as_ref().is_some_and(|profile| profile.has_background);
+        if has_background {
+            self.background_image = self.load_image(ImageKind::Background, self.artist.id);
+        } else {
+            self.background_image = None;
+        }
+
         for index in 0..4 {
-            if self.illusts[index].id != 0 {
-                let image_buf_illust = get_image_buf(&get_path_to_illust(self.illusts[index].id));
-                self.illust_images[index] = Some(image_buf_illust);
-            } else {
-                self.illust_images[index] = None;
-            }
+            self.load_illust_image(index);
         }
     }
 
+    /// Returns true if the work was uploaded after the current artist was last reviewed
+    pub fn is_new_work(&self, upload_date: &Option<DateTime<Utc>>) -> bool {
+        match (upload_date, self.last_reviewed_at) {
+            (Some(upload_date), Some(last_reviewed_at)) => *upload_date > last_reviewed_at,
+            _ => false,
+        }
+    }
+
+    /// Shows a blurred illustration as it is. Hidden illustrations stay hidden
+    pub fn reveal_illust(&mut self, illust_index: usize) {
+        if self.illust_filters[illust_index].is_empty() || self.settings.filter_mode == "hide" {
+            return
+        }
+
+        self.illust_images[illust_index] = self.load_image(ImageKind::Illust, self.illusts[illust_index].id);
+        self.illust_filters[illust_index] = String::new();
+    }
+
     /// Load all artist related information into the data
     pub fn change_artist(&mut self, artist_index: u16, redownload_if_exists: bool) {
-        if self.requires_initialization {
+        if self.requires_initialization && !self.is_offline {
             return
         }
 
-        let (artist_id, has_been_checked, total_artists) = match get_small_artist_info(artist_index) {
+        let (artist_id, has_been_checked, total_artists) = match get_small_artist_info(artist_index, !self.is_offline) {
             Some((i, s, l)) => (i, s, l),
             None => return,
         };
 
-        let artist_result = {
-            if redownload_if_exists || !does_path_exist(&format!("./jsons/{}.json", artist_id)) {
-                remove_old_artists(self.artist_index, self.max_downloaded_artists);
-                download_artist_info(&self.settings, artist_id)
-            } else {
-                load_artist_info(artist_id)
-            }
+        let cached_result = {
+            if redownload_if_exists && !self.is_offline { None }
+            else { load_artist_info(artist_id).filter(|ar| self.is_offline || !ar.is_outdated(&self.settings)) }
         };
 
-        self.artist = artist_result.artist;
-        self.last_bookmarked = artist_result.last_bookmarked;
-        self.illusts = artist_result.illusts;
+        let artist_result = match cached_result {
+            Some(ar) => ar,
+            None if self.is_offline => return,
+            None => match download_artist_info(&self.settings, artist_id) {
+                Some(ar) => ar,
+                None => {
+                    // pixiv can't be reached, so falling back to whatever is cached
+                    self.is_offline = true;
+                    match load_artist_info(artist_id) {
+                        Some(ar) => ar,
+                        None => return,
+                    }
+                },
+            },
+        };
+
+        // the download went through, so this is a good moment to send whatever is waiting
+        if !self.is_offline && !self.pending_changes.is_empty() {
+            self.flush_pending_changes();
+        }
+
+        touch_artist(artist_id);
+        if !self.is_offline {
+            enforce_cache_budget(&self.get_pinned_artist_ids(artist_index), get_max_cache_bytes(&self.settings), get_max_cached_artists(&self.settings));
+        }
+
+        // reloading the shown artist keeps the review time from before it was opened, so that the new works stay new
+        let is_same_artist = self.artist.id == artist_id && !self.is_looked_up;
+        if !is_same_artist {
+            self.last_reviewed_at = get_artist_entries().get(artist_index as usize).and_then(|entry| entry.reviewed_at);
+        }
+
+        let (summary, rule_matches) = self.show_artist_result(artist_result);
+        // browsing offline only shows what was cached, so artists.json is left as it is
+        if self.is_offline {
+            self.follow_visibility = get_artist_entries().get(artist_index as usize).map(|entry| entry.visibility.clone()).unwrap_or_else(default_visibility);
+        } else {
+            let mut visibility = default_visibility();
+            update_artist_entry(artist_index, |entry| {
+                entry.summary = Some(summary);
+                entry.rule_matches = rule_matches;
+                visibility = entry.visibility.clone();
+                if !is_same_artist {
+                    entry.reviewed_at = Some(Utc::now());
+                }
+            });
+            self.follow_visibility = visibility;
+        }
 
         self.artist_index = artist_index;
         self.total_artists = total_artists;
         self.has_been_checked = has_been_checked;
+        self.is_looked_up = false;
 
         self.load_window_title();
         self.load_images();
     }
 
+    /// Returns ids of the artists whose cached files should be kept: the artist at artist_index, the artists before and after it in the review queue, which are the ones that the user is most likely to look at next, and the shown looked up artist
+    fn get_pinned_artist_ids(&self, artist_index: u16) -> Vec<u32> {
+        let entries = get_artist_entries();
+        let queue = {
+            if self.review_queue.contains(&artist_index) { self.review_queue.to_vec() }
+            else { get_review_queue(&entries, &self.settings.queue_sort, &self.settings.queue_filter, &self.settings.activity_months, &self.settings.score_weights, self.is_offline, artist_index) }
+        };
+
+        let mut pinned: Vec<u32> = [Some(artist_index), find_next_in_queue(&queue, artist_index, false), find_next_in_queue(&queue, artist_index, true)]
+            .into_iter()
+            .flatten()
+            .filter_map(|index| entries.get(index as usize))
+            .map(|entry| entry.id)
+            .collect();
+
+        if self.is_looked_up {
+            pinned.push(self.artist.id);
+        }
+
+        pinned
+    }
+
+    /// Loads the artist info into the data. Returns the summary of the artist and what the review rules decided about them, so that they can be saved into artists.json
+    fn show_artist_result(&mut self, mut artist_result: ArtistResult) -> (ArtistSummary, RuleMatches) {
+        self.artist_bookmark_count = apply_bookmark_index(&mut artist_result);
+
+        let summary = ArtistSummary::from_result(&artist_result);
+        let rule_matches = evaluate_rules(&summary, &self.settings.score_weights);
+        self.affinity = calculate_affinity(&summary, &self.settings.score_weights);
+        self.matched_rules = rule_matches.matched_rules.join(", ");
+
+        self.illusts = artist_result.get_cards(&self.settings.card_mode);
+        self.artist = artist_result.artist;
+        self.last_bookmarked = artist_result.last_bookmarked;
+        self.novels = artist_result.novels.unwrap_or_default();
+        self.top_tags = Arc::new(artist_result.top_tags.unwrap_or_default());
+        self.tag_filter = String::new();
+        self.new_works_count = match (&artist_result.upload_dates, self.last_reviewed_at) {
+            (Some(upload_dates), Some(last_reviewed_at)) => upload_dates.iter().filter(|date| **date > last_reviewed_at).count(),
+            _ => 0,
+        };
+        self.activity = Arc::new(artist_result.activity.unwrap_or_default());
+        self.profile = artist_result.profile;
+        self.fetched_at = artist_result.fetched_at;
+
+        (summary, rule_matches)
+    }
+
+    /// Shows any pixiv artist by an id, a user url or an artwork url. Artists from the list are opened like with < and >, other artists are shown without being added to the list, and artist_index stays where it was
+    pub fn look_up_artist(&mut self) {
+        if !self.can_use_pixiv() {
+            self.lookup_status = "Pixiv can't be used right now".to_string();
+            return
+        }
+
+        let artist_id = match parse_lookup_input(&self.lookup_input) {
+            Some(LookupTarget::Artist(artist_id)) => artist_id,
+            Some(LookupTarget::Illust(illust_id)) => match get_illust_artist_id(&self.settings, illust_id) {
+                Some(artist_id) => artist_id,
+                None => {
+                    self.lookup_status = "Could not find that artwork".to_string();
+                    return
+                },
+            },
+            None => {
+                self.lookup_status = "Not an id or a pixiv url".to_string();
+                return
+            },
+        };
+
+        if let Some(artist_index) = find_artist_index(artist_id) {
+            self.lookup_status = String::new();
+            self.change_artist(artist_index, false);
+            return
+        }
+
+        self.show_looked_up_artist(artist_id);
+    }
+
+    /// Downloads and shows an artist that is not in the list
+    fn show_looked_up_artist(&mut self, artist_id: u32) {
+        let artist_result = match download_artist_info(&self.settings, artist_id) {
+            Some(ar) => ar,
+            None => {
+                self.lookup_status = "Could not download that artist".to_string();
+                return
+            },
+        };
+
+        touch_artist(artist_id);
+        // the looked up artist is not in the list, so nothing else keeps their files from being deleted
+        enforce_cache_budget(&[artist_id], get_max_cache_bytes(&self.settings), get_max_cached_artists(&self.settings));
+        self.last_reviewed_at = None;
+        self.show_artist_result(artist_result);
+        self.follow_visibility = default_visibility();
+        self.is_looked_up = true;
+        self.has_been_checked = false;
+        self.lookup_status = String::new();
+
+        self.window_title = format!("PAR - {} - not in the list", self.artist.name);
+        self.load_images();
+    }
+
+    /// Downloads the shown artist again. A looked up artist gets looked up again instead of reloading the artist at artist_index
+    pub fn reload_artist(&mut self) {
+        if !self.can_use_pixiv() {
+            return
+        }
+
+        if self.is_looked_up {
+            self.show_looked_up_artist(self.artist.id);
+        } else {
+            self.change_artist(self.artist_index, true);
+        }
+    }
+
+    /// Follows or unfollows the current artist. Following a looked up artist adds them to the end of the list as not reviewed
+    pub fn toggle_follow(&mut self) {
+        toggle_follow(self);
+
+        if self.is_looked_up && self.artist.is_followed {
+            let mut entry = ArtistEntry::new(self.artist.id, false);
+            entry.visibility = self.follow_visibility.clone();
+            if let Some(artist_result) = load_artist_info(self.artist.id) {
+                let summary = ArtistSummary::from_result(&artist_result);
+                entry.rule_matches = evaluate_rules(&summary, &self.settings.score_weights);
+                entry.summary = Some(summary);
+            }
+
+            self.artist_index = add_artist_entry(entry);
+            self.total_artists += 1;
+            self.is_looked_up = false;
+            self.load_window_title();
+        }
+    }
+
+    /// Moves to the next (or previous, if forward is false) artist in the review queue. The queue is kept as it was when the walk started, so that reviewing artists doesn't reorder it or filter them out. Artists that can't be opened, for example because they are not cached while offline, get skipped
+    pub fn step_artist(&mut self, forward: bool) {
+        // jumping to an artist outside of the queue starts a new walk from there
+        if !self.review_queue.contains(&self.artist_index) {
+            self.review_queue = Arc::new(get_review_queue(&get_artist_entries(), &self.settings.queue_sort, &self.settings.queue_filter, &self.settings.activity_months, &self.settings.score_weights, self.is_offline, self.artist_index));
+        }
+
+        let mut artist_index = self.artist_index;
+        while let Some(new_index) = find_next_in_queue(&self.review_queue, artist_index, forward) {
+            self.change_artist(new_index, false);
+            if self.artist_index == new_index {
+                return
+            }
+            artist_index = new_index;
+        }
+    }
+
+    /// Makes the next step_artist build the review queue again, for example because its sort or filter changed
+    pub fn forget_review_queue(&mut self) {
+        self.review_queue = Arc::new(Vec::new());
+    }
+
+    /// Loads the illustration and its existing bookmark into the bookmark window fields. If pixiv can't be used, the cached bookmark state is loaded instead, without tags and visibility
+    pub fn load_bookmark_details(&mut self, illust_index: usize) {
+        let illust_id = self.illusts[illust_index].id;
+        self.bookmark_illust = self.illusts[illust_index].clone();
+        self.bookmark_artist_id = self.artist.id;
+        self.bookmark_tags = String::new();
+        self.bookmark_comment = get_bookmark_comment(illust_id);
+        self.bookmark_is_private = false;
+        self.bookmark_details_loaded = false;
+
+        if !self.can_use_pixiv() {
+            self.bookmark_status = "Offline, tags and visibility can't be changed".to_string();
+            return
+        }
+
+        let bookmark_detail = match get_bookmark_detail(&self.settings, illust_id) {
+            Some(bd) => bd,
+            None => {
+                self.bookmark_status = "pixiv could not be reached, tags and visibility can't be changed".to_string();
+                return
+            },
+        };
+
+        self.bookmark_tags = bookmark_detail.tags.join(" ");
+        self.bookmark_is_private = bookmark_detail.restrict == "private";
+        self.bookmark_details_loaded = true;
+        self.bookmark_status = String::new();
+        // the bookmark could have been changed on pixiv in the meantime
+        if self.bookmark_illust.is_bookmarked != bookmark_detail.is_bookmarked {
+            self.show_bookmark_change(self.bookmark_artist_id, illust_id, bookmark_detail.is_bookmarked, &bookmark_detail.restrict);
+        }
+    }
+
+    /// Shows the new bookmark state of the illustration everywhere it is shown and saves it into the cached artist info and the bookmark index. The restrict is only used for new bookmarks
+    pub fn show_bookmark_change(&mut self, artist_id: u32, illust_id: u32, is_bookmarked: bool, restrict: &str) {
+        set_cached_bookmark(artist_id, illust_id, is_bookmarked);
+
+        let illust = self.illusts.iter().chain(std::iter::once(&self.bookmark_illust))
+            .find(|illust| illust.id == illust_id)
+            .cloned()
+            .unwrap_or_else(|| Illustration { id: illust_id, ..Illustration::load_empty() });
+        set_indexed_bookmark(artist_id, &illust, restrict, is_bookmarked);
+
+        if self.bookmark_illust.id == illust_id {
+            self.bookmark_illust.is_bookmarked = is_bookmarked;
+        }
+        if self.artist.id == artist_id {
+            for illust in self.illusts.iter_mut().filter(|illust| illust.id == illust_id) {
+                illust.is_bookmarked = is_bookmarked;
+            }
+        }
+    }
+
+    /// Bookmarks the illustration from the bookmark window with its tags, comment and visibility. If the bookmark details could not be loaded, only bookmarks it without tags, which gets queued while offline. Returns true if it worked
+    pub fn save_bookmark(&mut self) -> bool {
+        let artist_id = self.bookmark_artist_id;
+        let illust_id = self.bookmark_illust.id;
+
+        if !self.bookmark_details_loaded {
+            if !self.bookmark_illust.is_bookmarked && !change_bookmark(self, artist_id, illust_id, true) {
+                self.bookmark_status = "pixiv did not accept the bookmark".to_string();
+                return false
+            }
+            set_bookmark_comment(illust_id, &self.bookmark_comment);
+            self.bookmark_status = String::new();
+            return true
+        }
+
+        let restrict = {
+            if self.bookmark_is_private { "private" }
+            else { "public" }
+        };
+
+        match save_bookmark(&self.settings, illust_id, restrict, &parse_bookmark_tags(&self.bookmark_tags)) {
+            Some(true) => {
+                self.show_bookmark_change(artist_id, illust_id, true, restrict);
+                set_bookmark_comment(illust_id, &self.bookmark_comment);
+                self.bookmark_status = String::new();
+                true
+            },
+            Some(false) => {
+                self.bookmark_status = "pixiv did not accept the bookmark".to_string();
+                false
+            },
+            None => {
+                self.bookmark_status = "pixiv could not be reached".to_string();
+                false
+            },
+        }
+    }
+
+    /// Removes the bookmark of the illustration from the bookmark window. Gets queued while offline
+    pub fn remove_bookmark(&mut self) {
+        let illust_id = self.bookmark_illust.id;
+        if self.bookmark_illust.is_bookmarked && change_bookmark(self, self.bookmark_artist_id, illust_id, false) {
+            set_bookmark_comment(illust_id, "");
+        }
+    }
+
+    /// Starts downloading the user's bookmarks into the local bookmark index in the background. Unless full is true, only new bookmarks get downloaded
+    pub fn start_bookmark_sync(&mut self, sink: ExtEventSink, full: bool) {
+        if !self.can_use_pixiv() || is_bookmark_sync_running() {
+            return
+        }
+
+        start_bookmark_sync(sink, self.settings.clone(), full);
+        self.bookmark_index_report = "syncing...".to_string();
+    }
+
+    pub fn finish_bookmark_sync(&mut self, result: Option<usize>) {
+        match result {
+            Some(_) => {
+                self.bookmark_index_report = describe_bookmark_index(&load_bookmark_index(), &self.settings);
+                // the last bookmark of the current artist could have changed
+                if !self.is_looked_up {
+                    self.change_artist(self.artist_index, false);
+                }
+            },
+            None => self.bookmark_index_report = "sync failed, pixiv could not be reached".to_string(),
+        }
+    }
+
+    /// Starts a bulk bookmark action for the current artist in the background
+    pub fn start_batch(&mut self, sink: ExtEventSink, action: BatchAction) {
+        if !self.can_use_pixiv() || self.artist.id == 0 || is_batch_running() {
+            return
+        }
+
+        let known_ids: Vec<u32> = match action {
+            BatchAction::BookmarkShown => self.illusts.iter().filter(|illust| illust.id != 0 && !illust.is_bookmarked).map(|illust| illust.id).collect(),
+            BatchAction::BookmarkRecent => Vec::new(),
+            BatchAction::RemoveAll => {
+                let mut known_ids = get_indexed_bookmark_ids(self.artist.id);
+                let shown = self.illusts.iter().chain(std::iter::once(&self.last_bookmarked));
+                for illust in shown.filter(|illust| illust.id != 0 && illust.is_bookmarked) {
+                    if !known_ids.contains(&illust.id) {
+                        known_ids.push(illust.id);
+                    }
+                }
+                known_ids
+            },
+        };
+
+        if action == BatchAction::BookmarkShown && known_ids.is_empty() {
+            self.batch_status = "Already bookmarked".to_string();
+            return
+        }
+
+        start_batch(sink, self.settings.clone(), action, self.artist.id, known_ids);
+        self.batch_status = "Looking for works...".to_string();
+    }
+
+    pub fn cancel_batch(&mut self) {
+        cancel_batch();
+    }
+
+    pub fn apply_batch_progress(&mut self, progress: &BatchProgress) {
+        if progress.success {
+            self.show_bookmark_change(progress.artist_id, progress.illust_id, progress.target_state, "public");
+        }
+
+        self.batch_status = format!("{} {}/{}", if progress.target_state { "Bookmarking" } else { "Removing" }, progress.done, progress.total);
+    }
+
+    pub fn finish_batch(&mut self, result: &BatchResult) {
+        let verb = {
+            if result.action == BatchAction::RemoveAll { "Removed" }
+            else { "Bookmarked" }
+        };
+        self.batch_status = {
+            if result.failed > 0 { format!("{} {}, {} failed", verb, result.changed.len(), result.failed) }
+            else { format!("{} {}", verb, result.changed.len()) }
+        };
+        if !result.finished {
+            self.batch_status.push_str(", stopped");
+        }
+
+        // the last bookmarked illustration could have changed
+        if self.artist.id == result.artist_id && !result.changed.is_empty() {
+            self.reload_artist();
+        }
+    }
+
+    /// Shows the artist's most recent works with the tag in place of the most recent works. Filtering by the same tag again shows the most recent works again
+    pub fn toggle_tag_filter(&mut self, tag: &str) {
+        if self.tag_filter == tag {
+            if let Some(artist_result) = load_artist_info(self.artist.id) {
+                self.illusts = artist_result.get_cards(&self.settings.card_mode);
+            }
+            self.tag_filter = String::new();
+            self.load_images();
+            return
+        }
+
+        if !self.can_use_pixiv() {
+            return
+        }
+
+        match get_tagged_works(&self.settings, self.artist.id, tag) {
+            Some(tagged_works) => {
+                let mut illusts = Illustration::load_empty_list();
+                for (illust, tagged_work) in illusts.iter_mut().zip(tagged_works) {
+                    *illust = tagged_work;
+                }
+                self.illusts = illusts;
+                self.tag_filter = tag.to_string();
+                self.load_images();
+            },
+            None => {
+                self.is_offline = true;
+            },
+        }
+    }
+
+    /// Resets the unfollow window to the options that were used last time
+    pub fn load_unfollow_options(&mut self) {
+        self.unfollow_mute = self.settings.unfollow_mute;
+        self.unfollow_remove_bookmarks = self.settings.unfollow_remove_bookmarks;
+        self.unfollow_reason = String::new();
+    }
+
+    /// Unfollows the current artist with the options of the unfollow window, then remembers the options for next time. Muting and removing bookmarks need pixiv, so they get skipped if the app is offline, while the unfollow itself gets queued
+    pub fn unfollow_artist(&mut self, sink: ExtEventSink) {
+        if !self.artist.is_followed {
+            return
+        }
+
+        toggle_follow(self);
+        if self.artist.is_followed {
+            self.batch_status = "Could not unfollow".to_string();
+            return
+        }
+
+        // unfollowing switches to offline mode if pixiv could not be reached
+        let can_use_pixiv = self.can_use_pixiv();
+        let muted = self.unfollow_mute && can_use_pixiv && mute_artist(&self.settings, self.artist.id) == Some(true);
+        let removed_bookmarks = self.unfollow_remove_bookmarks && can_use_pixiv && !is_batch_running();
+
+        self.batch_status = {
+            if self.unfollow_mute && !muted { "Unfollowed, could not mute".to_string() }
+            else if muted { "Unfollowed and muted".to_string() }
+            else { "Unfollowed".to_string() }
+        };
+        if removed_bookmarks {
+            self.start_batch(sink, BatchAction::RemoveAll);
+        }
+
+        record_unfollow(UnfollowRecord {
+            artist_id: self.artist.id,
+            name: self.artist.name.clone(),
+            reason: self.unfollow_reason.trim().to_string(),
+            muted,
+            removed_bookmarks,
+            unfollowed_at: Utc::now(),
+        });
+
+        self.settings.unfollow_mute = self.unfollow_mute;
+        self.settings.unfollow_remove_bookmarks = self.unfollow_remove_bookmarks;
+        self.settings.save();
+        self.unfollow_reason = String::new();
+    }
+
+    /// Switches the follow of the current artist between public and private
+    pub fn toggle_follow_visibility(&mut self) {
+        if !self.can_use_pixiv() || !self.artist.is_followed {
+            return
+        }
+
+        let visibility = {
+            if self.follow_visibility == "private" { "public" }
+            else { "private" }
+        };
+
+        match set_follow_visibility(&self.settings, self.artist.id, visibility) {
+            Some(true) => {
+                self.follow_visibility = visibility.to_string();
+                if !self.is_looked_up {
+                    update_artist_entry(self.artist_index, |entry| entry.visibility = visibility.to_string());
+                }
+            },
+            Some(false) => {},
+            None => {
+                self.is_offline = true;
+            },
+        }
+    }
+
+    /// Switches to the next sort order of the review queue. Only changes the order of navigation, artists.json stays as it is
+    pub fn cycle_queue_sort(&mut self) {
+        self.settings.queue_sort = get_next_option(&QUEUE_SORTS, &self.settings.queue_sort);
+        self.settings.save();
+        self.forget_review_queue();
+    }
+
+    /// Switches to the next card mode and shows its cards for the current artist. The cards of every mode are in the cached info, only their images might have to be downloaded
+    pub fn cycle_card_mode(&mut self) {
+        self.settings.card_mode = get_next_option(&CARD_MODES, &self.settings.card_mode);
+        self.settings.save();
+
+        if let Some(artist_result) = load_artist_info(self.artist.id) {
+            self.illusts = artist_result.get_cards(&self.settings.card_mode);
+            self.tag_filter = String::new();
+            self.load_images();
+        }
+    }
+
+    pub fn cycle_queue_filter(&mut self) {
+        self.settings.queue_filter = get_next_option(&QUEUE_FILTERS, &self.settings.queue_filter);
+        self.settings.save();
+        self.forget_review_queue();
+        self.queue_filter_note = get_queue_filter_note(&get_artist_entries(), &self.settings.queue_filter, &self.settings.activity_months);
+    }
+
+    /// Switches between online and offline modes. Going online only succeeds if the token is valid and pixiv can be reached
+    pub fn toggle_offline_mode(&mut self) {
+        if self.is_offline {
+            if check_token_validity(&self.settings.token) {
+                self.is_offline = false;
+                self.is_offline_by_choice = false;
+                self.requires_initialization = false;
+                self.flush_pending_changes();
+            }
+        } else {
+            self.is_offline = true;
+            self.is_offline_by_choice = true;
+        }
+    }
+
+    /// Goes back online once the reconnect check could reach pixiv, and sends the pending changes. Stays offline if the user switched to the offline mode in the meantime
+    pub fn reconnect(&mut self) {
+        if !self.is_offline || self.is_offline_by_choice {
+            return
+        }
+
+        self.is_offline = false;
+        self.requires_initialization = false;
+        self.flush_pending_changes();
+    }
+
     pub fn load() -> AppData {
         let user_data = UserData::load();
         let requires_initialization = !check_token_validity(&user_data.token);
+        // there is no point in the offline mode if nothing has been downloaded yet
+        let is_offline = requires_initialization && !get_cached_artist_ids().is_empty();
+        let bookmark_index_report = describe_bookmark_index(&load_bookmark_index(), &user_data);
+        let queue_filter_note = get_queue_filter_note(&get_artist_entries(), &user_data.queue_filter, &user_data.activity_months);
 
         let mut data = AppData {
             requires_initialization: requires_initialization,
+            is_offline,
+            is_offline_by_choice: false,
             window_title: "<- Click on the S and change your token".to_string(),
             settings_window_is_open: false,
+            ranking_window_is_open: false,
+            list_window_is_open: false,
+            bookmark_window_is_open: false,
             info_window_is_open: false,
             jump_index: "0".to_string(),
             settings: user_data.clone(),
             temp_token: user_data.token,
             temp_amount_to_search: user_data.amount_to_search,
             temp_timezone: user_data.timezone,
-            max_downloaded_artists: 30,
+            temp_activity_months: user_data.activity_months,
+            temp_score_weights: user_data.score_weights,
+            temp_date_format: user_data.date_format,
+            temp_max_cache_size: user_data.max_cache_size,
+            temp_max_cached_artists: user_data.max_cached_artists,
+            temp_follow_list: user_data.follow_list,
+            temp_activity_works: user_data.activity_works,
+            temp_x_restrict_filter: user_data.x_restrict_filter,
+            temp_ai_filter: user_data.ai_filter,
+            temp_tag_blacklist: user_data.tag_blacklist,
+            temp_filter_mode: user_data.filter_mode,
+            cache_report: String::new(),
+            rules_report: get_rules_error().unwrap_or_default(),
+            is_looked_up: false,
+            lookup_input: String::new(),
+            lookup_status: String::new(),
+            bookmark_illust: Illustration::load_empty(),
+            bookmark_artist_id: 0,
+            bookmark_details_loaded: false,
+            bookmark_tags: String::new(),
+            bookmark_comment: String::new(),
+            bookmark_is_private: false,
+            bookmark_status: String::new(),
+            can_undo_review_marks: can_undo_mark_as_checked(),
+            unfollow_mute: user_data.unfollow_mute,
+            unfollow_remove_bookmarks: user_data.unfollow_remove_bookmarks,
+            unfollow_reason: String::new(),
+            list_search: String::new(),
+            list_sort: String::new(),
+            list_sort_descending: false,
+            bookmark_index_report,
+            batch_status: String::new(),
+            scan_status: match load_scan_progress() {
+                Some(progress) => format!("Scan paused at {}/{}", progress.next_index, progress.total_artists),
+                None => String::new(),
+            },
+            pending_changes: describe_outbox(),
             has_been_checked: false,
+            last_reviewed_at: None,
+            new_works_count: 0,
             total_artists: 0,
             artist_index: get_last_checked_artist_index(),
+            review_queue: Arc::new(Vec::new()),
+            queue_filter_note,
             pfp_image: None,
+            background_image: None,
             illust_images: [None, None, None, None],
+            missing_images: Arc::new(Vec::new()),
+            illust_filters: Default::default(),
             artist: Artist::load_empty(),
+            profile: None,
+            profile_is_expanded: false,
+            follow_visibility: default_visibility(),
             last_bookmarked: Illustration::load_empty(),
+            artist_bookmark_count: None,
             illusts: Illustration::load_empty_list(),
+            novels: Vec::new(),
+            shows_novels: false,
+            top_tags: Arc::new(Vec::new()),
+            tag_filter: String::new(),
+            affinity: 0.0,
+            matched_rules: String::new(),
+            activity: Arc::new(Vec::new()),
+            fetched_at: None,
         };
 
+        data.flush_pending_changes();
         data.change_artist(data.artist_index, false);
+        if data.is_offline && data.artist.id == 0 {
+            let cached_index = find_cached_artist_index(data.artist_index, true)
+                .or_else(|| find_cached_artist_index(data.artist_index, false));
+            if let Some(cached_index) = cached_index {
+                data.change_artist(cached_index, false);
+            }
+        }
         data
     }
 
@@ -188,14 +1365,14 @@ impl AppData {
         let mut do_reset_artist_list = false;
 
         self.temp_token = self.temp_token.trim().to_string();
-        self.temp_amount_to_search = self.temp_amount_to_search.trim().to_string();
-        self.temp_timezone = self.temp_timezone.trim().to_string();
 
         if self.settings.token != self.temp_token {
             let is_valid = check_token_validity(&self.temp_token);
             if is_valid {
                 self.settings.token = self.temp_token.clone();
                 self.requires_initialization = false;
+                self.is_offline = false;
+                self.is_offline_by_choice = false;
                 changes_made = true;
                 do_reset_artist_list = true;
             } else {
@@ -203,37 +1380,27 @@ impl AppData {
             }
         }
 
-        if self.settings.amount_to_search != self.temp_amount_to_search {
-            if self.temp_amount_to_search.len() == 0 {
-                self.temp_amount_to_search = ui::DEFAULT_AMOUNT_TO_SEARCH.to_string();
-                self.settings.amount_to_search = ui::DEFAULT_AMOUNT_TO_SEARCH.to_string();
-                changes_made = true;
-            } else {
-                let is_valid = check_amount_to_search_validity(&self.temp_amount_to_search);
-                if is_valid {
-                    self.settings.amount_to_search = self.temp_amount_to_search.clone();
-                    changes_made = true;
-                } else {
-                    self.temp_amount_to_search = self.settings.amount_to_search.clone();
-                }
-            }
-        }
-
-        if self.settings.timezone != self.temp_timezone {
-            if self.temp_timezone.len() == 0 {
-                self.temp_timezone = ui::DEFAULT_TIMEZONE.to_string();
-                self.settings.timezone = ui::DEFAULT_TIMEZONE.to_string();
-                changes_made = true;
-            } else {
-                let is_valid = check_timezone_validity(&self.temp_timezone);
-                if is_valid {
-                    self.settings.timezone = self.temp_timezone.clone();
-                    changes_made = true;
-                } else {
-                    self.temp_timezone = self.settings.timezone.clone();
-                }
-            }
+        changes_made |= apply_setting(&mut self.settings.amount_to_search, &mut self.temp_amount_to_search, ui::DEFAULT_AMOUNT_TO_SEARCH, check_amount_to_search_validity);
+        changes_made |= apply_setting(&mut self.settings.timezone, &mut self.temp_timezone, ui::DEFAULT_TIMEZONE, check_timezone_validity);
+        // "06" and "6" are the same amount of months, so it gets saved as "6"
+        if let Ok(months) = self.temp_activity_months.trim().parse::<u16>() {
+            self.temp_activity_months = months.to_string();
         }
+        let activity_months_changed = apply_setting(&mut self.settings.activity_months, &mut self.temp_activity_months, ui::DEFAULT_ACTIVITY_MONTHS, check_activity_months_validity);
+        changes_made |= activity_months_changed;
+        let score_weights_changed = apply_setting(&mut self.settings.score_weights, &mut self.temp_score_weights, ui::DEFAULT_SCORE_WEIGHTS, check_score_weights_validity);
+        changes_made |= score_weights_changed;
+        changes_made |= apply_setting(&mut self.settings.date_format, &mut self.temp_date_format, ui::DEFAULT_DATE_FORMAT, check_date_format_validity);
+        changes_made |= apply_setting(&mut self.settings.max_cache_size, &mut self.temp_max_cache_size, ui::DEFAULT_MAX_CACHE_SIZE, check_cache_size_validity);
+        changes_made |= apply_setting(&mut self.settings.max_cached_artists, &mut self.temp_max_cached_artists, ui::DEFAULT_MAX_CACHED_ARTISTS, check_cached_artists_validity);
+        changes_made |= apply_setting(&mut self.settings.follow_list, &mut self.temp_follow_list, ui::DEFAULT_FOLLOW_LIST, check_follow_list_validity);
+        let activity_works_changed = apply_setting(&mut self.settings.activity_works, &mut self.temp_activity_works, ui::DEFAULT_ACTIVITY_WORKS, check_activity_works_validity);
+        changes_made |= activity_works_changed;
+        let mut content_filters_changed = apply_setting(&mut self.settings.x_restrict_filter, &mut self.temp_x_restrict_filter, ui::DEFAULT_X_RESTRICT_FILTER, check_x_restrict_filter_validity);
+        content_filters_changed |= apply_setting(&mut self.settings.ai_filter, &mut self.temp_ai_filter, ui::DEFAULT_AI_FILTER, check_ai_filter_validity);
+        content_filters_changed |= apply_setting(&mut self.settings.tag_blacklist, &mut self.temp_tag_blacklist, "", |_| true);
+        content_filters_changed |= apply_setting(&mut self.settings.filter_mode, &mut self.temp_filter_mode, ui::DEFAULT_FILTER_MODE, check_filter_mode_validity);
+        changes_made |= content_filters_changed;
 
         if changes_made {
             self.settings.save();
@@ -241,6 +1408,11 @@ impl AppData {
 
         if do_reset_artist_list {
             reset_artist_list(self);
+        } else if activity_months_changed || activity_works_changed || score_weights_changed {
+            // the cached info might have been counted for the old window, so it gets downloaded again if needed, and the score gets recalculated
+            self.change_artist(self.artist_index, false);
+        } else if content_filters_changed {
+            self.load_images();
         }
     }
 
@@ -249,6 +1421,162 @@ impl AppData {
         self.temp_token = self.settings.token.clone();
         self.temp_amount_to_search = self.settings.amount_to_search.clone();
         self.temp_timezone = self.settings.timezone.clone();
+        self.temp_activity_months = self.settings.activity_months.clone();
+        self.temp_score_weights = self.settings.score_weights.clone();
+        self.temp_date_format = self.settings.date_format.clone();
+        self.temp_max_cache_size = self.settings.max_cache_size.clone();
+        self.temp_max_cached_artists = self.settings.max_cached_artists.clone();
+        self.temp_follow_list = self.settings.follow_list.clone();
+        self.temp_activity_works = self.settings.activity_works.clone();
+        self.temp_x_restrict_filter = self.settings.x_restrict_filter.clone();
+        self.temp_ai_filter = self.settings.ai_filter.clone();
+        self.temp_tag_blacklist = self.settings.tag_blacklist.clone();
+        self.temp_filter_mode = self.settings.filter_mode.clone();
+    }
+
+    pub fn refresh_pending_changes(&mut self) {
+        self.pending_changes = describe_outbox();
+    }
+
+    /// Sends the bookmarks and follows that were made while offline. If pixiv can't be reached, goes back to offline mode
+    pub fn flush_pending_changes(&mut self) {
+        if !self.can_use_pixiv() {
+            return
+        }
+
+        let still_pending = flush_outbox(&self.settings);
+        if still_pending > 0 {
+            self.is_offline = true;
+        }
+        self.refresh_pending_changes();
+    }
+
+    /// Discards the bookmarks and follows that were not sent yet and reverts them in the cached artist info, the latest change first
+    pub fn clear_pending_changes(&mut self) {
+        for item in clear_outbox().into_iter().rev() {
+            match item.action {
+                OutboxAction::Bookmark { artist_id, illust_id, restrict } => self.show_bookmark_change(artist_id, illust_id, !item.target_state, &restrict),
+                OutboxAction::NovelBookmark { artist_id, novel_id } => set_cached_novel_bookmark(artist_id, novel_id, !item.target_state),
+                OutboxAction::Follow { artist_id, .. } => set_cached_follow(artist_id, !item.target_state),
+            }
+        }
+
+        self.refresh_pending_changes();
+        self.change_artist(self.artist_index, false);
+    }
+
+    /// Saves the summary of an artist from the bulk scan into artists.json, together with the results of the review rules
+    pub fn apply_scan_update(&mut self, update: &ScanUpdate) {
+        if !is_scan_active() {
+            return
+        }
+
+        let artist_result = match &update.artist_result {
+            Some(ar) => ar,
+            None => {
+                save_scan_progress(&ScanProgress { next_index: update.artist_index, total_artists: update.total_artists });
+                self.scan_status = format!("Scan paused at {}/{}, pixiv could not be reached", update.artist_index, update.total_artists);
+                return
+            },
+        };
+
+        let mut artist_result = artist_result.clone();
+        apply_bookmark_index(&mut artist_result);
+
+        let summary = ArtistSummary::from_result(&artist_result);
+        let rule_matches = evaluate_rules(&summary, &self.settings.score_weights);
+        update_artist_entry(update.artist_index, |entry| {
+            // the list could have been reloaded in the meantime
+            if entry.id == update.artist_id {
+                entry.summary = Some(summary);
+                entry.rule_matches = rule_matches;
+            }
+        });
+
+        let next_index = update.artist_index + 1;
+        save_scan_progress(&ScanProgress { next_index, total_artists: update.total_artists });
+        self.scan_status = {
+            if is_scan_paused() { format!("Scan paused at {}/{}", next_index, update.total_artists) }
+            else { format!("Scanning {}/{}", next_index, update.total_artists) }
+        };
+    }
+
+    pub fn finish_scan(&mut self, sink: ExtEventSink, finished: bool) {
+        if mark_scan_stopped() {
+            self.start_scan(sink, true);
+            return
+        }
+
+        if finished {
+            remove_scan_progress();
+            self.scan_status = "Scan finished".to_string();
+            self.queue_filter_note = get_queue_filter_note(&get_artist_entries(), &self.settings.queue_filter, &self.settings.activity_months);
+        } else if load_scan_progress().is_none() {
+            self.scan_status = "Scan cancelled".to_string();
+        }
+    }
+
+    /// Starts scanning the whole artist list from the beginning, or resumes the paused scan
+    pub fn start_scan(&mut self, sink: ExtEventSink, from_beginning: bool) {
+        if !self.can_use_pixiv() {
+            return
+        }
+
+        if from_beginning {
+            remove_scan_progress();
+        }
+        self.scan_status = {
+            if start_scan(sink, self.settings.clone(), from_beginning) { "Scanning".to_string() }
+            else if from_beginning { "Restarting the scan".to_string() }
+            else { "Scan cancelled".to_string() }
+        };
+    }
+
+    pub fn pause_scan(&mut self) {
+        pause_scan();
+        if is_scan_paused() {
+            self.scan_status = match load_scan_progress() {
+                Some(progress) => format!("Scan paused at {}/{}", progress.next_index, progress.total_artists),
+                None => "Scan paused".to_string(),
+            };
+        }
+    }
+
+    /// Returns true if the scan button should resume a scan instead of pausing it
+    pub fn can_resume_scan(&self) -> bool {
+        can_resume_scan()
+    }
+
+    pub fn cancel_scan(&mut self) {
+        cancel_scan();
+        self.scan_status = "Scan cancelled".to_string();
+    }
+
+    /// Evaluates the review rules for every artist with downloaded info and for the current artist
+    pub fn reevaluate_rules(&mut self) {
+        self.rules_report = match reevaluate_all_rules(&self.settings.score_weights) {
+            Ok(candidates) => format!("{} unfollow candidates", candidates),
+            Err(e) => e,
+        };
+        self.change_artist(self.artist_index, false);
+    }
+
+    /// Removes broken cache entries, which works offline too. Missing images get queued for a background download when pixiv can be used. Reloads the current artist's images in case they were among them
+    pub fn verify_cache(&mut self) {
+        let removed = remove_broken_cache_files();
+        self.load_images();
+
+        if self.can_use_pixiv() {
+            let missing = Arc::make_mut(&mut self.missing_images);
+            for image in find_missing_images() {
+                if !missing.contains(&image) {
+                    missing.push(image);
+                }
+            }
+            self.cache_report = format!("removed {}, downloading {}", removed, missing.len());
+        } else {
+            self.cache_report = format!("removed {}, offline so nothing was downloaded", removed);
+        }
     }
 
     pub fn temp_token_has_changed(&self) -> bool {
@@ -276,12 +1604,27 @@ impl AppData {
         }
     }
 
-    /// Jumps to artist at jump_index
+    /// Jumps to artist at jump_index. Only navigates, the review state of other artists stays as it is
     pub fn jump_to_jump_index(&mut self) {
         let new_index = self.get_jump_index().unwrap();
-        mark_as_checked_up_to_index(new_index);
         self.change_artist(new_index, false);
     }
+
+    /// Marks all artists before jump_index as reviewed
+    pub fn mark_as_reviewed_before_jump_index(&mut self) {
+        let new_index = self.get_jump_index().unwrap();
+        mark_as_checked_before_index(new_index);
+        self.can_undo_review_marks = can_undo_mark_as_checked();
+    }
+
+    /// Brings back the review state from before the last mark_as_reviewed_before_jump_index
+    pub fn undo_review_marks(&mut self) {
+        undo_mark_as_checked();
+        self.can_undo_review_marks = can_undo_mark_as_checked();
+        if let Some((_, has_been_checked, _)) = get_small_artist_info(self.artist_index, false) {
+            self.has_been_checked = has_been_checked;
+        }
+    }
 }
 
 impl Artist {
@@ -290,19 +1633,25 @@ impl Artist {
             name: "Artist name".to_string(),
             id: 0,
             recent_count: 0,
+            recent_months: default_recent_months(),
             is_followed: false,
+            recent_novel_count: 0,
+            counts_novels: false,
         }
     }
 }
 
 impl Illustration {
-    fn load_empty() -> Illustration {
+    pub fn load_empty() -> Illustration {
         Illustration {
             id: 0,
             views: 0,
             bookmarks: 0,
-            upload_date: "Upload date".to_string(),
+            upload_date: None,
             is_bookmarked: false,
+            tags: Vec::new(),
+            x_restrict: None,
+            is_ai: false,
         }
     }
 
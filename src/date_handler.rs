@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer};
+use crate::data::UserData;
+use crate::ui_globals::ui;
+
+/// Parses a date from the artist json. Dates that can't be parsed (like the preformatted strings from older versions of the app) become None instead of failing the whole json
+pub fn deserialize_date<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date: Option<String> = Option::deserialize(deserializer)?;
+    Ok(date.and_then(|date| DateTime::parse_from_rfc3339(&date).ok()).map(|date| date.with_timezone(&Utc)))
+}
+
+/// Returns the timezone from the settings. An empty timezone means pixiv's timezone
+fn get_timezone(data: &UserData) -> Tz {
+    let timezone = {
+        if data.timezone.is_empty() { ui::DEFAULT_TIMEZONE }
+        else { &data.timezone }
+    };
+    timezone.parse().unwrap_or(Tz::Asia__Tokyo)
+}
+
+/// Returns true if timezone is a valid IANA timezone name, otherwise returns false
+pub fn check_timezone_validity(timezone: &str) -> bool {
+    timezone.parse::<Tz>().is_ok()
+}
+
+/// Returns true if date_format is one of the supported date formats, otherwise returns false
+pub fn check_date_format_validity(date_format: &str) -> bool {
+    date_format == "absolute" || date_format == "relative"
+}
+
+/// Returns how long ago the date was, for example "3 days ago"
+fn format_relative_date(date: &DateTime<Utc>) -> String {
+    let seconds = (Utc::now() - *date).num_seconds().max(0);
+
+    let (amount, unit) = {
+        if seconds < 60 { return "just now".to_string() }
+        else if seconds < 60 * 60 { (seconds / 60, "minute") }
+        else if seconds < 60 * 60 * 24 { (seconds / (60 * 60), "hour") }
+        else if seconds < 60 * 60 * 24 * 30 { (seconds / (60 * 60 * 24), "day") }
+        else if seconds < 60 * 60 * 24 * 365 { (seconds / (60 * 60 * 24 * 30), "month") }
+        else { (seconds / (60 * 60 * 24 * 365), "year") }
+    };
+
+    if amount == 1 { format!("1 {} ago", unit) }
+    else { format!("{} {}s ago", amount, unit) }
+}
+
+/// Formats the date according to the settings. Returns placeholder if there is no date
+pub fn format_date(date: &Option<DateTime<Utc>>, data: &UserData, placeholder: &str) -> String {
+    let date = match date {
+        Some(d) => d,
+        None => return placeholder.to_string(),
+    };
+
+    if data.date_format == "relative" {
+        format_relative_date(date)
+    } else {
+        date.with_timezone(&get_timezone(data)).format("%Y/%m/%d %H:%M:%S").to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+    use serde::Deserialize;
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Dated {
+        #[serde(default, deserialize_with = "deserialize_date")]
+        date: Option<DateTime<Utc>>,
+    }
+
+    fn parse_date(json: &str) -> Option<DateTime<Utc>> {
+        serde_json::from_str::<Dated>(json).unwrap().date
+    }
+
+    #[test]
+    fn rfc3339_dates_are_parsed_as_utc() {
+        assert_eq!(parse_date(r#"{"date": "2024-03-01T09:00:00+09:00"}"#), Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+        assert_eq!(parse_date(r#"{"date": "2024-03-01T00:00:00Z"}"#), Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn old_and_missing_dates_become_none() {
+        assert_eq!(parse_date(r#"{"date": "2024/03/01 09:00:00"}"#), None);
+        assert_eq!(parse_date(r#"{"date": null}"#), None);
+        assert_eq!(parse_date("{}"), None);
+    }
+
+    #[test]
+    fn timezones_are_iana_names() {
+        assert!(check_timezone_validity("Asia/Tokyo"));
+        assert!(check_timezone_validity("UTC"));
+        assert!(!check_timezone_validity("Tokyo"));
+        assert!(!check_timezone_validity("+09:00"));
+        assert!(!check_timezone_validity(""));
+    }
+
+    #[test]
+    fn date_formats_are_absolute_or_relative() {
+        assert!(check_date_format_validity("absolute"));
+        assert!(check_date_format_validity("relative"));
+        assert!(!check_date_format_validity("Relative"));
+        assert!(!check_date_format_validity(""));
+    }
+
+    #[test]
+    fn dates_are_formatted_by_the_settings() {
+        let date = Some(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+        let mut data = UserData { timezone: "Asia/Tokyo".to_string(), date_format: "absolute".to_string(), ..UserData::load_default() };
+        assert_eq!(format_date(&date, &data, ""), "2024/03/01 09:00:00");
+        assert_eq!(format_date(&None, &data, "never"), "never");
+
+        data.date_format = "relative".to_string();
+        assert_eq!(format_date(&Some(Utc::now() - Duration::days(3)), &data, ""), "3 days ago");
+        assert_eq!(format_date(&Some(Utc::now() - Duration::hours(1)), &data, ""), "1 hour ago");
+    }
+}
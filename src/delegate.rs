@@ -0,0 +1,57 @@
+use druid::{AppDelegate, Command, DelegateCtx, Env, Event, Handled, Target, WindowId};
+use crate::batch_handler::{BATCH_PROGRESS, BATCH_DONE};
+use crate::bookmark_handler::BOOKMARK_SYNC_DONE;
+use crate::cache_handler::{IMAGE_DOWNLOADED, start_image_download};
+use crate::data::AppData;
+use crate::outbox_handler::{PIXIV_REACHABLE, is_reconnect_check_running, start_reconnect_check};
+use crate::scan_handler::{SCAN_UPDATE, SCAN_STOPPED};
+
+/// Applies results that background threads send to the app
+pub struct Delegate;
+
+/// Hands the images that load_image could not find to the background download
+fn download_missing_images(ctx: &mut DelegateCtx, data: &mut AppData) {
+    if !data.missing_images.is_empty() {
+        start_image_download(ctx.get_external_handle(), data.settings.clone(), data.take_missing_images());
+    }
+}
+
+impl AppDelegate<AppData> for Delegate {
+    fn event(&mut self, ctx: &mut DelegateCtx, _: WindowId, event: Event, data: &mut AppData, _: &Env) -> Option<Event> {
+        // pixiv keeps getting checked in the background while the app is offline because pixiv could not be reached, so that the pending changes get sent once it's back
+        if data.is_offline && !data.is_offline_by_choice && !is_reconnect_check_running() {
+            start_reconnect_check(ctx.get_external_handle(), data.settings.token.clone());
+        }
+        download_missing_images(ctx, data);
+        Some(event)
+    }
+
+    fn command(&mut self, ctx: &mut DelegateCtx, _: Target, cmd: &Command, data: &mut AppData, _: &Env) -> Handled {
+        download_missing_images(ctx, data);
+
+        if let Some(update) = cmd.get(SCAN_UPDATE) {
+            data.apply_scan_update(update);
+            Handled::Yes
+        } else if let Some(finished) = cmd.get(SCAN_STOPPED) {
+            data.finish_scan(ctx.get_external_handle(), *finished);
+            Handled::Yes
+        } else if let Some(progress) = cmd.get(BATCH_PROGRESS) {
+            data.apply_batch_progress(progress);
+            Handled::Yes
+        } else if let Some(result) = cmd.get(BATCH_DONE) {
+            data.finish_batch(result);
+            Handled::Yes
+        } else if let Some(result) = cmd.get(BOOKMARK_SYNC_DONE) {
+            data.finish_bookmark_sync(*result);
+            Handled::Yes
+        } else if let Some((kind, id)) = cmd.get(IMAGE_DOWNLOADED) {
+            data.show_downloaded_image(*kind, *id);
+            Handled::Yes
+        } else if cmd.is(PIXIV_REACHABLE) {
+            data.reconnect();
+            Handled::Yes
+        } else {
+            Handled::No
+        }
+    }
+}
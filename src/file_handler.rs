@@ -1,8 +1,12 @@
 use druid::ImageBuf;
+use std::collections::HashMap;
 use std::path::Path;
-use std::fs::{create_dir, remove_file};
-use crate::data::{ArtistResult, AppData, UserData};
-use crate::pixiv_handler::reset_artist_list;
+use std::fs::{create_dir, remove_file, read_dir, rename};
+use crate::data::{ArtistResult, AppData, UserData, ArtistEntry};
+use crate::pixiv_handler::{reset_artist_list, ImageKind};
+
+/// Review state of every artist from before the last bulk marking, as (id, is_checked) pairs
+const REVIEW_UNDO_PATH: &str = "./review_undo.json";
 
 /// Checks if settings.json exists. Starts an initializing sequence if it doesn't
 pub fn prepare_settings() -> AppData {
@@ -36,19 +40,62 @@ pub fn does_path_exist(path: &str) -> bool {
     Path::new(path).exists()
 }
 
-/// Reads artists.json and returns the contents on successfull read. Returns None if an error occurs
-fn get_artist_list() -> Option<Vec<(u32, bool)>> {
+/// Reads artists.json and returns the contents on successfull read. Returns None if an error occurs. Lists saved by older versions of the app only had (id, is_checked) pairs, those get converted
+fn get_artist_list() -> Option<Vec<ArtistEntry>> {
     let file_contents = std::fs::read_to_string("artists.json").ok()?;
-    serde_json::from_str(&file_contents).ok()
+
+    match serde_json::from_str(&file_contents) {
+        Ok(list) => Some(list),
+        Err(_) => {
+            let old_list: Vec<(u32, bool)> = serde_json::from_str(&file_contents).ok()?;
+            Some(old_list.into_iter().map(|(id, is_checked)| ArtistEntry::new(id, is_checked)).collect())
+        },
+    }
 }
 
-fn save_artist_list(list: &Vec<(u32, bool)>) {
+fn save_artist_list(list: &[ArtistEntry]) {
     let file_cont = serde_json::to_string(list)
         .expect("Could not convert data to json");
     std::fs::write("artists.json", file_cont)
         .expect("Could not write to artists.json");
 }
 
+/// Returns all entries of artists.json. Returns an empty list if artists.json is invalid
+pub fn get_artist_entries() -> Vec<ArtistEntry> {
+    get_artist_list().unwrap_or_default()
+}
+
+/// Replaces all entries of artists.json
+pub fn save_artist_entries(entries: &[ArtistEntry]) {
+    save_artist_list(entries);
+}
+
+/// Changes the entry of the artist at artist_index in artists.json with the provided function and saves the list
+pub fn update_artist_entry(artist_index: u16, update: impl FnOnce(&mut ArtistEntry)) {
+    let mut artist_list = match get_artist_list() {
+        Some(al) => al,
+        None => return,
+    };
+
+    if let Some(entry) = artist_list.get_mut(artist_index as usize) {
+        update(entry);
+        save_artist_list(&artist_list);
+    }
+}
+
+/// Returns the index of the artist in artists.json. Returns None if the artist is not in the list
+pub fn find_artist_index(artist_id: u32) -> Option<u16> {
+    get_artist_list()?.iter().position(|entry| entry.id == artist_id).map(|index| index as u16)
+}
+
+/// Adds the entry to the end of artists.json, so that the indexes of the other artists stay the same. Returns the index of the entry
+pub fn add_artist_entry(entry: ArtistEntry) -> u16 {
+    let mut artist_list = get_artist_list().unwrap_or_default();
+    artist_list.push(entry);
+    save_artist_list(&artist_list);
+    (artist_list.len() - 1) as u16
+}
+
 /// Returns [index - 1] of the first occurence of false in artists.json. Returns 0 if artists.json is invalid
 pub fn get_last_checked_artist_index() -> u16 {
     let artist_list = match get_artist_list() {
@@ -57,7 +104,7 @@ pub fn get_last_checked_artist_index() -> u16 {
     };
 
     for index in 1..artist_list.len() {
-        if !artist_list[index].1 {
+        if !artist_list[index].is_checked {
             return (index-1) as u16;
         }
     }
@@ -65,8 +112,8 @@ pub fn get_last_checked_artist_index() -> u16 {
     (artist_list.len()-1) as u16
 }
 
-/// Returns artist id, whether the artist has been checked or not, and artist.json length. Returns None if the artist_index is out of range or if artist.json is invalid. Also marks the artist as checked
-pub fn get_small_artist_info(artist_index: u16) -> Option<(u32, bool, u16)> {
+/// Returns artist id, whether the artist has been checked or not, and artist.json length. Returns None if the artist_index is out of range or if artist.json is invalid. Also marks the artist as checked if mark_as_checked is true
+pub fn get_small_artist_info(artist_index: u16, mark_as_checked: bool) -> Option<(u32, bool, u16)> {
     let mut artist_list = match get_artist_list() {
         Some(al) => al,
         None => return None,
@@ -79,93 +126,233 @@ pub fn get_small_artist_info(artist_index: u16) -> Option<(u32, bool, u16)> {
         return None;
     }
 
-    let (id, status) = artist_list[index];
+    let id = artist_list[index].id;
+    let status = artist_list[index].is_checked;
 
-    if !artist_list[index].1 {
-        artist_list[index].1 = true;
+    if mark_as_checked && !artist_list[index].is_checked {
+        artist_list[index].is_checked = true;
         save_artist_list(&artist_list);
     };
 
     Some((id, status, count as u16))
 }
 
-/// load artist info from disk
-pub fn load_artist_info(artist_id: u32) -> ArtistResult {
-    let path = format!("./jsons/{}.json", artist_id);
-    let file_cont = std::fs::read_to_string(&path)
-        .expect(&format!("Could not read {}", path));
-    let artist_result: ArtistResult = serde_json::from_str(&file_cont)
-        .expect(&format!("Could not parse {}", path));
+/// Loads artist info from disk. Returns None if the json is missing or can't be parsed
+pub fn load_artist_info(artist_id: u32) -> Option<ArtistResult> {
+    let file_cont = std::fs::read_to_string(get_path_to_artist_json(artist_id)).ok()?;
+    serde_json::from_str(&file_cont).ok()
+}
 
-    artist_result
+/// Saves artist info to disk. Writes to a temporary file first so that an interrupted write never leaves a broken json behind
+pub fn save_artist_info(artist_info: &ArtistResult) {
+    let path = get_path_to_artist_json(artist_info.artist.id);
+    let temp_path = format!("{}.part", path);
+    let file_cont = serde_json::to_string(artist_info)
+        .expect("Could not convert data to json");
+    std::fs::write(&temp_path, file_cont)
+        .expect(&format!("Could not write to {}", temp_path));
+    rename(&temp_path, &path)
+        .expect(&format!("Could not write to {}", path));
 }
 
-/// Marks artists in range 0..artist_index as checked, and then marks all artists after that as not checked
-pub fn mark_as_checked_up_to_index(artist_index: u16) {
-    let artist_index = artist_index as usize;
-    let mut list = get_artist_list().unwrap();
-    for index in 0..list.len() {
-        if index < artist_index {
-            list[index].1 = true;
-        } else {
-            list[index].1 = false;
+/// Updates the bookmark status of the illustration in the cached artist info, if it is cached
+pub fn set_cached_bookmark(artist_id: u32, illust_id: u32, is_bookmarked: bool) {
+    if let Some(mut artist_info) = load_artist_info(artist_id) {
+        let card_sets = artist_info.card_sets.iter_mut().flat_map(|card_sets| card_sets.values_mut()).flatten();
+        for illust in artist_info.illusts.iter_mut().chain(std::iter::once(&mut artist_info.last_bookmarked)).chain(card_sets) {
+            if illust.id == illust_id {
+                illust.is_bookmarked = is_bookmarked;
+            }
         }
+        save_artist_info(&artist_info);
+    }
+}
+
+/// Updates the bookmark status of the novel in the cached artist info, if it is cached
+pub fn set_cached_novel_bookmark(artist_id: u32, novel_id: u32, is_bookmarked: bool) {
+    if let Some(mut artist_info) = load_artist_info(artist_id) {
+        for novel in artist_info.novels.iter_mut().flatten() {
+            if novel.id == novel_id {
+                novel.is_bookmarked = is_bookmarked;
+            }
+        }
+        save_artist_info(&artist_info);
+    }
+}
+
+/// Updates the follow status in the cached artist info, if it is cached
+pub fn set_cached_follow(artist_id: u32, is_followed: bool) {
+    if let Some(mut artist_info) = load_artist_info(artist_id) {
+        artist_info.artist.is_followed = is_followed;
+        save_artist_info(&artist_info);
+    }
+}
+
+/// Marks artists in range 0..artist_index as checked. The previous review state of every artist gets saved first, so that undo_mark_as_checked can bring it back
+pub fn mark_as_checked_before_index(artist_index: u16) {
+    let mut list = match get_artist_list() {
+        Some(al) => al,
+        None => return,
+    };
+
+    let previous_state: Vec<(u32, bool)> = list.iter().map(|entry| (entry.id, entry.is_checked)).collect();
+    let file_cont = serde_json::to_string(&previous_state)
+        .expect("Could not convert data to json");
+    std::fs::write(REVIEW_UNDO_PATH, file_cont)
+        .expect("Could not write to review_undo.json");
+
+    for entry in list.iter_mut().take(artist_index as usize) {
+        entry.is_checked = true;
     }
     save_artist_list(&list);
 }
 
-fn delete_artist_info_if_exists(artist_id: u32) {
-    // have to do this because sometimes pixiv returns artist and profile picture with id 0 and the app downloads it
-    if artist_id != 0 {
-        delete_artist_info_if_exists(0);
+/// Returns true if there is a bulk review marking that can be undone
+pub fn can_undo_mark_as_checked() -> bool {
+    does_path_exist(REVIEW_UNDO_PATH)
+}
+
+/// Restores the review state from before the last mark_as_checked_before_index. Artists that were added to the list since then are left as they are. Returns false if there is nothing to undo
+pub fn undo_mark_as_checked() -> bool {
+    let previous_state: Vec<(u32, bool)> = match std::fs::read_to_string(REVIEW_UNDO_PATH).ok().and_then(|fc| serde_json::from_str(&fc).ok()) {
+        Some(ps) => ps,
+        None => return false,
+    };
+    let previous_state: HashMap<u32, bool> = previous_state.into_iter().collect();
+
+    if let Some(mut list) = get_artist_list() {
+        for entry in list.iter_mut() {
+            if let Some(is_checked) = previous_state.get(&entry.id) {
+                entry.is_checked = *is_checked;
+            }
+        }
+        save_artist_list(&list);
     }
 
-    let json_path = format!("./jsons/{}.json", artist_id);
-    if !does_path_exist(&json_path) {
-        return;
+    let _ = remove_file(REVIEW_UNDO_PATH);
+    true
+}
+
+/// Returns the index of the closest artist in artists.json after (or before, if forward is false) artist_index whose info is cached on disk. Returns None if there is no such artist
+pub fn find_cached_artist_index(artist_index: u16, forward: bool) -> Option<u16> {
+    let artist_list = get_artist_list()?;
+    let index = artist_index as usize;
+
+    let is_cached = |(_, entry): &(usize, &ArtistEntry)| does_path_exist(&get_path_to_artist_json(entry.id));
+
+    let found = {
+        if forward { artist_list.iter().enumerate().skip(index + 1).find(is_cached) }
+        else { artist_list.iter().enumerate().take(index).rev().find(is_cached) }
+    };
+
+    found.map(|(i, _)| i as u16)
+}
+
+/// Loads the image at path. Returns None if the image is missing or can't be decoded, in which case the broken file also gets deleted so that it can be downloaded again
+pub fn get_image_buf(path: &str) -> Option<ImageBuf> {
+    let bytes = std::fs::read(path).ok()?;
+    match ImageBuf::from_data(&bytes) {
+        Ok(image_data) => Some(image_data),
+        Err(_) => {
+            let _ = remove_file(path);
+            None
+        },
     }
+}
+
+/// Returns ids of all artists that have a json in the jsons folder
+pub fn get_cached_artist_ids() -> Vec<u32> {
+    let entries = match read_dir("./jsons") {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
 
-    let artist_info = load_artist_info(artist_id);
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            file_name.strip_suffix(".json")?.parse::<u32>().ok()
+        })
+        .collect()
+}
 
-    for index in 0..4 {
-        let illust_id = artist_info.illusts[index].id;
-        if illust_id != 0 {
-            let image_path = get_path_to_illust(illust_id);
-            match remove_file(image_path) { _ => {} };
+/// Removes leftover temporary files from interrupted downloads from the folder. If check_images is true, also removes all images that can't be decoded. Returns the amount of removed files
+fn remove_broken_files(folder: &str, check_images: bool) -> usize {
+    let entries = match read_dir(folder) {
+        Ok(e) => e,
+        Err(_) => return 0,
+    };
+
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path().to_string_lossy().to_string();
+
+        if path.ends_with(".part") {
+            let _ = remove_file(&path);
+            removed += 1;
+        } else if check_images && path.ends_with(".jpeg") && get_image_buf(&path).is_none() {
+            // get_image_buf already deletes the file
+            removed += 1;
         }
     }
 
-    let image_path = get_path_to_pfp(artist_id);
-    match remove_file(image_path) { _ => {} };
-    match remove_file(json_path) { _ => {} };
+    removed
 }
 
-/// Deletes old artists' json and images
-pub fn remove_old_artists(current_artist_index: u16, max_allowed_amount: usize) {
-    let artist_index = current_artist_index as usize;
-    if artist_index+2 <= max_allowed_amount {
-        return;
+/// Scans the images and jsons folders and removes temporary files, undecodable images and unparsable jsons. Doesn't need pixiv. Returns the amount of removed files
+pub fn remove_broken_cache_files() -> usize {
+    let mut removed = remove_broken_files("./images", true) + remove_broken_files("./jsons", false);
+
+    for artist_id in get_cached_artist_ids() {
+        if load_artist_info(artist_id).is_none() && remove_file(get_path_to_artist_json(artist_id)).is_ok() {
+            removed += 1;
+        }
     }
 
-    let delete_up_to = artist_index + 2 - max_allowed_amount;
-    let list = get_artist_list().unwrap();
-    for index in 0..delete_up_to {
-        delete_artist_info_if_exists(list[index].0);
+    removed
+}
+
+/// Returns the images that are referenced by the cached jsons but are missing, so that they can be downloaded again
+pub fn find_missing_images() -> Vec<(ImageKind, u32)> {
+    let mut missing = Vec::new();
+
+    for artist_id in get_cached_artist_ids() {
+        let artist_info = match load_artist_info(artist_id) {
+            Some(ai) => ai,
+            None => continue,
+        };
+
+        if !does_path_exist(&get_path_to_pfp(artist_id)) {
+            missing.push((ImageKind::Pfp, artist_id));
+        }
+
+        let has_background = artist_info.profile.as_ref().is_some_and(|profile| profile.has_background);
+        if has_background && !does_path_exist(&get_path_to_background(artist_id)) {
+            missing.push((ImageKind::Background, artist_id));
+        }
+
+        for illust in artist_info.illusts.iter() {
+            if illust.id != 0 && !does_path_exist(&get_path_to_illust(illust.id)) {
+                missing.push((ImageKind::Illust, illust.id));
+            }
+        }
     }
+
+    missing
 }
 
-pub fn get_image_buf(path: &str) -> ImageBuf {
-    let bytes = std::fs::read(&path)
-        .expect(&format!("Could not open an image at \"{}\"", path));
-    let image_data = ImageBuf::from_data(&bytes)
-        .expect(&format!("Could not load the image at \"{}\"", path));
-    image_data
+pub fn get_path_to_artist_json(id: u32) -> String {
+    format!("./jsons/{}.json", id)
 }
 
 pub fn get_path_to_pfp(id: u32) -> String {
     format!("./images/u_{}.jpeg", id)
 }
 
+pub fn get_path_to_background(id: u32) -> String {
+    format!("./images/b_{}.jpeg", id)
+}
+
 pub fn get_path_to_illust(id: u32) -> String {
     format!("./images/i_{}.jpeg", id)
 }
@@ -1,11 +1,12 @@
 from pixivpy3 import AppPixivAPI, PixivError
 import json
 import sys
-from datetime import datetime, timedelta
-from pytz import UnknownTimeZoneError, timezone
-from os import listdir, path, remove
+from datetime import datetime, timedelta, timezone
+from os import listdir, path, remove, replace
 
 CURR_DIR = path.curdir
+# how many of the artist's most used tags get kept in the artist json
+TOP_TAG_COUNT = 8
 
 ##### GENERAL FUNCTIONS #####
 
@@ -27,32 +28,88 @@ def format_artist_json(original_json):
         "name": original_json["name"],
         "id": original_json["id"],
         "recent_count": 0,
+        "recent_months": 0,
         "is_followed": original_json["is_followed"]
     }
 
-def format_illust_json(original_json, tz):
+# dates are returned as they come from pixiv, timezone conversion and formatting happen on the Rust side
+def format_illust_json(original_json):
     return {
         "id": original_json["id"],
         "views": original_json["total_view"],
         "bookmarks": original_json["total_bookmarks"],
-        "upload_date": str(
-            datetime.strptime(original_json["create_date"], "%Y-%m-%dT%H:%M:%S%z").astimezone(tz).strftime("%Y/%m/%d %H:%M:%S")
-        ),
+        "upload_date": original_json["create_date"],
+        "is_bookmarked": original_json["is_bookmarked"],
+        "tags": [tag["name"] for tag in original_json["tags"]],
+        "x_restrict": original_json["x_restrict"],
+        # illust_ai_type is 0 when unknown, 1 when not ai-generated and 2 when ai-generated
+        "is_ai": original_json.get("illust_ai_type") == 2
+    }
+
+def format_profile_json(original_json):
+    user = original_json["user"]
+    profile = original_json["profile"]
+    social_links = [url for url in (profile.get("twitter_url"), profile.get("pawoo_url")) if url]
+
+    return {
+        "comment": user.get("comment") or "",
+        "webpage": profile.get("webpage") or "",
+        "social_links": social_links,
+        "total_illusts": profile["total_illusts"],
+        "total_manga": profile["total_manga"],
+        "total_novels": profile["total_novels"],
+        "is_premium": profile["is_premium"],
+        "region": profile.get("region") or "",
+        "has_background": profile.get("background_image_url") is not None
+    }
+
+def format_novel_json(original_json):
+    return {
+        "id": original_json["id"],
+        "title": original_json["title"],
+        "text_length": original_json["text_length"],
+        "views": original_json["total_view"],
+        "bookmarks": original_json["total_bookmarks"],
+        "upload_date": original_json["create_date"],
         "is_bookmarked": original_json["is_bookmarked"]
     }
 
+# pads the cards with empty illusts, so that there are always 4 of them
+def format_cards(original_illusts):
+    cards = [format_illust_json(illust) for illust in original_illusts[:4]]
+    for i in range(len(cards), 4):
+        cards.append(create_empty_illust())
+    return cards
+
 def create_empty_illust():
     return {
         "id": 0,
         "views": 0,
         "bookmarks": 0,
-        "upload_date": "Upload date",
-        "is_bookmarked": False
+        "upload_date": None,
+        "is_bookmarked": False,
+        "tags": [],
+        "x_restrict": 0,
+        "is_ai": False
     }
 
+# writes to a temporary file first so that an interrupted write never leaves a broken json behind
 def save_result(result):
-    with open(f"{CURR_DIR}\\jsons\\{result['artist']['id']}.json", "w") as fh:
+    json_path = f"{CURR_DIR}\\jsons\\{result['artist']['id']}.json"
+    with open(f"{json_path}.part", "w") as fh:
         json.dump(result, fh)
+    replace(f"{json_path}.part", json_path)
+
+# downloads into a temporary file first so that an interrupted download never leaves a broken image behind
+def download_image(client, url, filename):
+    image_path = f"{CURR_DIR}\\images"
+    client.download(
+        url,
+        path = image_path,
+        fname = f"{filename}.part",
+        replace = True
+    )
+    replace(f"{image_path}\\{filename}.part", f"{image_path}\\{filename}")
 
 def load_result(artist_id):
     with open(f"{CURR_DIR}\\jsons\\{artist_id}.json") as fh:
@@ -67,39 +124,47 @@ def delete_artist_info_if_exists(artist_id):
     if not path.isfile(json_path):
         return
 
-    with open(json_path) as fh:
-        file = json.load(fh)
+    # a broken json only needs to be removed, its images will be overwritten by the new download anyway
+    try:
+        with open(json_path) as fh:
+            file = json.load(fh)
+    except ValueError:
+        remove(json_path)
+        return
 
     for illust in file["illusts"]:
-        if illust["id"] != 0:
-            remove(f"{CURR_DIR}\\images\\i_{illust['id']}.jpeg")
+        remove_if_exists(f"{CURR_DIR}\\images\\i_{illust['id']}.jpeg")
+    for cards in file.get("card_sets", {}).values():
+        for illust in cards:
+            remove_if_exists(f"{CURR_DIR}\\images\\i_{illust['id']}.jpeg")
 
-    remove(f"{CURR_DIR}\\images\\u_{artist_id}.jpeg")
+    remove_if_exists(f"{CURR_DIR}\\images\\u_{artist_id}.jpeg")
+    remove_if_exists(f"{CURR_DIR}\\images\\b_{artist_id}.jpeg")
     remove(json_path)
 
+def remove_if_exists(file_path):
+    if path.isfile(file_path):
+        remove(file_path)
+
 def delete_all_artist_info_if_exists():
     json_path = f"{CURR_DIR}\\jsons"
     if path.exists(json_path):
         for file in listdir(json_path):
-            if file.endswith(".json"):
+            if file.endswith(".json") or file.endswith(".part"):
                 remove(f"{json_path}\\{file}")
 
     image_path = f"{CURR_DIR}\\images"
     if path.exists(image_path):
         for file in listdir(image_path):
-            if file.endswith(".jpeg"):
+            if file.endswith(".jpeg") or file.endswith(".part"):
                 remove(f"{image_path}\\{file}")
 
 ##### DOWNLOAD ARTIST LIST #####
 
-def download_artist_list(token, page_limit=0):
-    delete_all_artist_info_if_exists()
-
-    client = initialize_client(token)
+def download_followed_artists(client, visibility, page_limit):
     artist_list = []
 
-    #artists = client.user_following(client.user_id, "private")
-    artists = client.user_following(client.user_id)
+    artists = client.user_following(client.user_id, visibility)
 
     page_count = 0
     while True:
@@ -108,7 +173,7 @@ def download_artist_list(token, page_limit=0):
             break
 
         for artist in artists["user_previews"]:
-            artist_list.append([artist["user"]["id"], False])
+            artist_list.append({"id": artist["user"]["id"], "is_checked": False, "visibility": visibility})
 
         try:
             artists = get_next_page(client, artists)
@@ -119,22 +184,61 @@ def download_artist_list(token, page_limit=0):
             break
 
     artist_list.reverse()
+    return artist_list
+
+# follow_list is either "public" or "all". Private follows go after the public ones, since pixiv lists them separately
+def download_artist_list(token, follow_list="public", page_limit=0):
+    delete_all_artist_info_if_exists()
+
+    client = initialize_client(token)
+    artist_list = download_followed_artists(client, "public", page_limit)
+    if follow_list == "all":
+        artist_list += download_followed_artists(client, "private", page_limit)
+
     with open(f"{CURR_DIR}\\artists.json", "w") as fh:
         json.dump(artist_list, fh)
 
 ##### DOWNLOAD ARTIST INFO #####
 
-def prepare_dai(token, id, amount_to_search, tz):
+def prepare_dai(token, id, amount_to_search, activity_months, activity_works="all", card_mode="recent"):
     amount_to_search = int(amount_to_search)
-    if len(tz) == 0:
-        tz = timezone("Etc/GMT-9")
-    else:
-        tz = timezone(tz)
+    activity_months = int(activity_months)
 
     delete_artist_info_if_exists(id)
-    download_artist_info(token, id, amount_to_search, tz)
+    download_artist_info(token, id, amount_to_search, activity_months, activity_works, card_mode)
+
+# same as download_artist_info, but without downloading images and without saving the result. Used by the bulk scan
+def scan_artist_info(token, id, amount_to_search, activity_months, activity_works="all"):
+    amount_to_search = int(amount_to_search)
+    activity_months = int(activity_months)
+
+    download_artist_info(token, id, amount_to_search, activity_months, activity_works, scan_only=True)
+
+# novels are only searched within the activity window, since they are only needed for counting recent works
+def count_recent_novels(client, result, amount_to_search, date_threshold, activity, counts_novels):
+    recent_novel_count = 0
+    total_count = 0
 
-def download_artist_info(token, artist_id, amount_to_search, tz):
+    while result is not None:
+        for novel in result["novels"]:
+            total_count += 1
+            if total_count > amount_to_search or novel["create_date"] < date_threshold:
+                return recent_novel_count
+
+            recent_novel_count += 1
+            if counts_novels:
+                month = novel["create_date"][:7]
+                if month not in activity:
+                    activity[month] = {"month": month, "uploads": 0, "bookmarked": 0}
+                activity[month]["uploads"] += 1
+                if novel["is_bookmarked"]:
+                    activity[month]["bookmarked"] += 1
+
+        result = get_next_page(client, result)
+
+    return recent_novel_count
+
+def download_artist_info(token, artist_id, amount_to_search, activity_months, activity_works="all", card_mode="recent", scan_only=False):
     client = initialize_client(token)
     result = client.user_illusts(artist_id)
 
@@ -145,26 +249,23 @@ def download_artist_info(token, artist_id, amount_to_search, tz):
     #print("Got artist")
 
     # illusts
-    illust_urls = []
-    illusts = []
-    for i in range(min(4, len(result["illusts"]))):
-        illust_urls.append(result["illusts"][i]["image_urls"]["square_medium"])
-        illust = format_illust_json(result["illusts"][i], tz)
-        illusts.append(illust)
-
-    for i in range(len(illusts), 4):
-        illusts.append(create_empty_illust())
+    recent_illusts = result["illusts"][:4]
+    illusts = format_cards(recent_illusts)
 
     #print("Got illusts")
 
-    # last bookmarked && recent count
-    date_threshold = (datetime.now() - timedelta(days=30*6)).strftime("%Y-%m-%d")
-    count_recent = True
+    # last bookmarked && recent count && monthly activity
+    # the whole searched range has to be paged through for the monthly activity to be complete
+    date_threshold = (datetime.now() - timedelta(days=30*activity_months)).strftime("%Y-%m-%d")
     recent_count = 0
 
     found_bookmarked = False
+    last_bookmarked_position = None
     while_break = False
     total_count = 0
+    activity = {}
+    tag_counts = {}
+    searched = []
 
     while True:
         for illust in result["illusts"]:
@@ -173,22 +274,27 @@ def download_artist_info(token, artist_id, amount_to_search, tz):
                 while_break = True
                 break
 
-            if count_recent:
-                if illust["create_date"] < date_threshold:
-                    count_recent = False
-                    if found_bookmarked:
-                        while_break = True
-                        break
-                else:
-                    recent_count += 1
+            searched.append(illust)
+            if illust["create_date"] >= date_threshold:
+                recent_count += 1
 
             if not found_bookmarked:
                 if illust["is_bookmarked"]:
-                    last_bookmarked = format_illust_json(illust, tz)
+                    last_bookmarked = format_illust_json(illust)
+                    last_bookmarked_position = total_count - 1
                     found_bookmarked = True
-                    if not count_recent:
-                        while_break = True
-                        break
+
+            month = illust["create_date"][:7]
+            if month not in activity:
+                activity[month] = {"month": month, "uploads": 0, "bookmarked": 0}
+            activity[month]["uploads"] += 1
+            if illust["is_bookmarked"]:
+                activity[month]["bookmarked"] += 1
+
+            for tag in illust["tags"]:
+                if tag["name"] not in tag_counts:
+                    tag_counts[tag["name"]] = {"name": tag["name"], "translated_name": tag.get("translated_name"), "count": 0}
+                tag_counts[tag["name"]]["count"] += 1
 
         if while_break:
             break
@@ -200,82 +306,124 @@ def download_artist_info(token, artist_id, amount_to_search, tz):
     if not found_bookmarked:
         last_bookmarked = create_empty_illust()
 
-    artist["recent_count"] = recent_count
+    # card sets, the most recent works stay in "illusts" since the summary and the review rules are based on them
+    original_card_sets = {
+        "recent": recent_illusts,
+        "popular": sorted(searched, key=lambda illust: illust["total_bookmarks"], reverse=True)[:4],
+        "unbookmarked": [illust for illust in searched if not illust["is_bookmarked"]][:4],
+        "bookmarked": [illust for illust in searched if illust["is_bookmarked"]][:4]
+    }
+    card_sets = {mode: format_cards(cards) for mode, cards in original_card_sets.items() if mode != "recent"}
 
-    #print("Got last bookmarked and recent count")
+    # novels
+    counts_novels = activity_works == "all"
+    novel_result = client.user_novels(artist_id)
+    novels = [format_novel_json(novel) for novel in novel_result["novels"][:4]]
+    recent_novel_count = count_recent_novels(client, novel_result, amount_to_search, date_threshold, activity, counts_novels)
 
-    # image download
-    image_path = f"{CURR_DIR}\\images"
+    artist["recent_count"] = recent_count + recent_novel_count if counts_novels else recent_count
+    artist["recent_months"] = activity_months
+    artist["recent_novel_count"] = recent_novel_count
+    artist["counts_novels"] = counts_novels
 
-    filename = f"u_{artist['id']}.jpeg"
-    client.download(
-        artist_pfp_url,
-        path = image_path,
-        fname = filename,
-        replace = True
-    )
+    #print("Got last bookmarked and recent count")
 
-    for i in range(4):
-        if illusts[i]["id"] == 0:
-            break
+    # profile details, scans only need the numbers that get summarized, so they skip the extra request
+    profile = None
+    if not scan_only:
+        user_detail = client.user_detail(artist_id)
+        profile = format_profile_json(user_detail)
 
-        filename = f"i_{illusts[i]['id']}.jpeg"
-        client.download(
-            illust_urls[i],
-            path = image_path,
-            fname = filename,
-            replace = True
-        )
+    # image download
+    if not scan_only:
+        download_image(client, artist_pfp_url, f"u_{artist['id']}.jpeg")
+        if profile["has_background"]:
+            download_image(client, user_detail["profile"]["background_image_url"], f"b_{artist['id']}.jpeg")
+
+        # images of the other card modes get downloaded when they are first shown
+        for illust in original_card_sets.get(card_mode, recent_illusts):
+            download_image(client, illust["image_urls"]["square_medium"], f"i_{illust['id']}.jpeg")
 
     #print("Downloaded all images")
 
     result = {
         "artist": artist,
         "last_bookmarked": last_bookmarked,
-        "illusts": illusts
+        "last_bookmarked_position": last_bookmarked_position,
+        "illusts": illusts,
+        "card_sets": card_sets,
+        "novels": novels,
+        "activity": sorted(activity.values(), key=lambda a: a["month"]),
+        # ties keep the order the tags were first seen in, so more recently used tags come first
+        "top_tags": sorted(tag_counts.values(), key=lambda t: t["count"], reverse=True)[:TOP_TAG_COUNT],
+        # used for counting the works that were uploaded since the artist was last reviewed
+        "upload_dates": [illust["create_date"] for illust in searched],
+        # used for removing bookmarks from the bookmark index that were removed outside of PAR
+        "unbookmarked_ids": [illust["id"] for illust in searched if not illust["is_bookmarked"]],
+        "profile": profile,
+        "fetched_at": datetime.now(timezone.utc).isoformat()
     }
 
-    save_result(result)
+    if not scan_only:
+        save_result(result)
     return_result(result)
 
-##### TOGGLE BOOKMARK #####
-
-def toggle_bookmark(token, artist_id, illust_index):
-    illust_index = int(illust_index)
+##### GET TAGGED WORKS #####
 
-    artist_json = load_result(artist_id)
-    illust_id = artist_json["illusts"][illust_index]["id"]
-    is_bookmarked = artist_json["illusts"][illust_index]["is_bookmarked"]
+# the works are not saved to the artist json, their images only stay until the next cache cleanup
+def get_tagged_works(token, artist_id, amount_to_search, hex_tag):
+    amount_to_search = int(amount_to_search)
+    tag = json.loads(bytes.fromhex(hex_tag).decode("utf-8"))
 
     client = initialize_client(token)
-    old_bookmark_status = client.illust_bookmark_detail(illust_id)["bookmark_detail"]["is_bookmarked"]
+    result = client.user_illusts(artist_id)
 
-    success = False
+    illusts = []
+    total_count = 0
+    while result is not None and len(illusts) < 4:
+        for illust in result["illusts"]:
+            total_count += 1
+            if total_count > amount_to_search or len(illusts) == 4:
+                result = None
+                break
 
-    # in case there is desync and the bookmark has already been toggled
-    if is_bookmarked != old_bookmark_status:
-        success = True
+            if any(t["name"] == tag for t in illust["tags"]):
+                illusts.append(format_illust_json(illust))
+                if not path.isfile(f"{CURR_DIR}\\images\\i_{illust['id']}.jpeg"):
+                    download_image(client, illust["image_urls"]["square_medium"], f"i_{illust['id']}.jpeg")
 
-    # otherwise, just bookmarking and checking if it was successfull
-    else:
-        if is_bookmarked:
-            client.illust_bookmark_delete(illust_id)
-        else:
-            client.illust_bookmark_add(illust_id)
+        if result is not None:
+            result = get_next_page(client, result)
 
-        new_bookmark_status = client.illust_bookmark_detail(illust_id)["bookmark_detail"]["is_bookmarked"]
-        if old_bookmark_status != new_bookmark_status:
-            success = True
+    return_result(illusts)
 
-    if success:
-        artist_json["illusts"][illust_index]["is_bookmarked"] = not is_bookmarked
-        save_result(artist_json)
+##### REDOWNLOAD IMAGE #####
 
-    return_result(success)
+def redownload_image(token, kind, id):
+    client = initialize_client(token)
+
+    try:
+        if kind == "pfp":
+            url = client.user_detail(id)["user"]["profile_image_urls"]["medium"]
+            download_image(client, url, f"u_{id}.jpeg")
+        elif kind == "background":
+            url = client.user_detail(id)["profile"]["background_image_url"]
+            download_image(client, url, f"b_{id}.jpeg")
+        elif kind == "illust":
+            url = client.illust_detail(id)["illust"]["image_urls"]["square_medium"]
+            download_image(client, url, f"i_{id}.jpeg")
+        else:
+            raise Exception("Unknown image kind:", kind)
+        result = True
+    except (PixivError, KeyError, TypeError):
+        result = False
+
+    return_result(result)
 
 ##### TOGGLE FOLLOW #####
 
-def toggle_follow(token, artist_id):
+# visibility is only used when following, it is either "public" or "private"
+def toggle_follow(token, artist_id, visibility="public"):
     artist_json = load_result(artist_id)
     is_followed = artist_json["artist"]["is_followed"]
 
@@ -293,7 +441,7 @@ def toggle_follow(token, artist_id):
         if is_followed:
             client.user_follow_delete(artist_id)
         else:
-            client.user_follow_add(artist_id)
+            client.user_follow_add(artist_id, visibility)
 
         new_follow_status = client.user_detail(artist_id)["user"]["is_followed"]
         if old_follow_status != new_follow_status:
@@ -305,24 +453,219 @@ def toggle_follow(token, artist_id):
 
     return_result(success)
 
-##### VALIDATE TOKEN #####
+##### SET BOOKMARK #####
 
-def validate_token(token):
+# takes the intended final state, so that a bookmark that was already changed from somewhere else does not get toggled back. A deleted illustration makes pixiv answer with an error instead of the bookmark detail, which returns false, since retrying would not help
+def set_bookmark(token, illust_id, state):
+    state = state == "true"
+
+    client = initialize_client(token)
     try:
-        initialize_client(token)
-        result = True
-    except PixivError:
+        current_status = client.illust_bookmark_detail(illust_id)["bookmark_detail"]["is_bookmarked"]
+
+        # the bookmark might have already been changed from somewhere else in the meantime
+        if current_status != state:
+            if state:
+                client.illust_bookmark_add(illust_id)
+            else:
+                client.illust_bookmark_delete(illust_id)
+
+            current_status = client.illust_bookmark_detail(illust_id)["bookmark_detail"]["is_bookmarked"]
+        result = current_status == state
+    except (KeyError, TypeError):
         result = False
 
     return_result(result)
 
-##### VALIDATE TIMEZONE #####
+##### SET NOVEL BOOKMARK #####
 
-def validate_timezone(tz):
+# pixivpy has no functions for novel bookmarks, so the endpoints are called directly. Deleted novels return false, like in set_bookmark
+def set_novel_bookmark(token, novel_id, state):
+    state = state == "true"
+
+    client = initialize_client(token)
     try:
-        timezone(tz)
+        current_status = client.novel_detail(novel_id)["novel"]["is_bookmarked"]
+
+        # the bookmark might have already been changed from somewhere else in the meantime
+        if current_status != state:
+            if state:
+                client.no_auth_requests_call("POST", f"{client.hosts}/v2/novel/bookmark/add", data={"novel_id": novel_id, "restrict": "public"}, req_auth=True)
+            else:
+                client.no_auth_requests_call("POST", f"{client.hosts}/v1/novel/bookmark/delete", data={"novel_id": novel_id}, req_auth=True)
+
+            current_status = client.novel_detail(novel_id)["novel"]["is_bookmarked"]
+        result = current_status == state
+    except (KeyError, TypeError):
+        result = False
+
+    return_result(result)
+
+##### BOOKMARK DETAILS #####
+
+def get_bookmark_detail(token, illust_id):
+    client = initialize_client(token)
+    bookmark_detail = client.illust_bookmark_detail(illust_id)["bookmark_detail"]
+
+    return_result({
+        "is_bookmarked": bookmark_detail["is_bookmarked"],
+        "restrict": bookmark_detail["restrict"],
+        "tags": [tag["name"] for tag in bookmark_detail["tags"] if tag["is_registered"]]
+    })
+
+# tags come hex encoded, since cmd would interpret characters like & and | in them. Bookmarking an illustration that is already bookmarked replaces its tags and visibility
+def save_bookmark(token, illust_id, restrict, hex_tags):
+    tags = json.loads(bytes.fromhex(hex_tags).decode("utf-8"))
+
+    client = initialize_client(token)
+    client.illust_bookmark_add(illust_id, restrict, tags)
+
+    return_result(client.illust_bookmark_detail(illust_id)["bookmark_detail"]["is_bookmarked"])
+
+##### DOWNLOAD BOOKMARKS #####
+
+# returns the user's bookmarks with the given restrict, most recently bookmarked first. Stops at the bookmark with stop_at_id, so that only new bookmarks get downloaded. stop_at_id "0" downloads all of them
+def download_bookmarks(token, restrict, stop_at_id):
+    stop_at_id = int(stop_at_id)
+
+    client = initialize_client(token)
+    result = client.user_bookmarks_illust(client.user_id, restrict)
+
+    bookmarks = []
+    while_break = False
+    while True:
+        for illust in result["illusts"]:
+            if illust["id"] == stop_at_id:
+                while_break = True
+                break
+
+            # deleted works have no user
+            if illust["user"]["id"] == 0:
+                continue
+
+            bookmark = format_illust_json(illust)
+            bookmark["artist_id"] = illust["user"]["id"]
+            bookmark["restrict"] = restrict
+            bookmarks.append(bookmark)
+
+        if while_break:
+            break
+
+        result = get_next_page(client, result)
+        if result is None:
+            break
+
+    return_result(bookmarks)
+
+##### BULK BOOKMARKS #####
+
+# returns ids of the artist's works uploaded in the last `months` months that the user hasn't bookmarked yet
+def get_recent_works(token, artist_id, months):
+    months = int(months)
+    date_threshold = (datetime.now() - timedelta(days=30*months)).strftime("%Y-%m-%d")
+
+    client = initialize_client(token)
+    result = client.user_illusts(artist_id)
+
+    illust_ids = []
+    while_break = False
+    while True:
+        for illust in result["illusts"]:
+            if illust["create_date"] < date_threshold:
+                while_break = True
+                break
+
+            if not illust["is_bookmarked"]:
+                illust_ids.append(illust["id"])
+
+        if while_break:
+            break
+
+        result = get_next_page(client, result)
+        if result is None:
+            break
+
+    return_result(illust_ids)
+
+# returns ids of all of the artist's works that the user has bookmarked. Unlike download_artist_info, searches through all of the works
+def get_bookmarked_works(token, artist_id):
+    client = initialize_client(token)
+    result = client.user_illusts(artist_id)
+
+    illust_ids = []
+    while result is not None:
+        for illust in result["illusts"]:
+            if illust["is_bookmarked"]:
+                illust_ids.append(illust["id"])
+
+        result = get_next_page(client, result)
+
+    return_result(illust_ids)
+
+##### SET FOLLOW #####
+
+# suspended or deleted users return false, like in set_bookmark
+def set_follow(token, artist_id, state, visibility="public"):
+    state = state == "true"
+
+    client = initialize_client(token)
+    try:
+        current_status = client.user_detail(artist_id)["user"]["is_followed"]
+
+        # the follow might have already been changed from somewhere else in the meantime
+        if current_status != state:
+            if state:
+                client.user_follow_add(artist_id, visibility)
+            else:
+                client.user_follow_delete(artist_id)
+
+            current_status = client.user_detail(artist_id)["user"]["is_followed"]
+        result = current_status == state
+    except (KeyError, TypeError):
+        result = False
+
+    return_result(result)
+
+##### GET ILLUST ARTIST #####
+
+# used for looking up the artist of an artwork url. Returns null if the illustration doesn't exist
+def get_illust_artist(token, illust_id):
+    client = initialize_client(token)
+    illust = client.illust_detail(illust_id).get("illust")
+
+    if illust is None:
+        return_result(None)
+    else:
+        return_result(illust["user"]["id"])
+
+##### MUTE ARTIST #####
+
+# pixivpy has no function for muting, so the endpoint is called directly. Free accounts can only mute a limited amount of users, in which case pixiv refuses
+def mute_artist(token, artist_id):
+    client = initialize_client(token)
+    r = client.no_auth_requests_call("POST", f"{client.hosts}/v1/mute/edit", data={"add_user_ids[]": [artist_id]}, req_auth=True)
+
+    return_result(r.status_code == 200)
+
+##### SET FOLLOW VISIBILITY #####
+
+# following an artist that is already followed only changes the visibility of the follow
+def set_follow_visibility(token, artist_id, visibility):
+    client = initialize_client(token)
+    if not client.user_detail(artist_id)["user"]["is_followed"]:
+        return_result(False)
+        return
+
+    client.user_follow_add(artist_id, visibility)
+    return_result(True)
+
+##### VALIDATE TOKEN #####
+
+def validate_token(token):
+    try:
+        initialize_client(token)
         result = True
-    except UnknownTimeZoneError:
+    except PixivError:
         result = False
 
     return_result(result)
@@ -335,14 +678,38 @@ def main():
 
     if command == "download_artist_info":
         prepare_dai(*args)
+    elif command == "scan_artist_info":
+        scan_artist_info(*args)
     elif command == "download_artist_list":
         download_artist_list(*args)
-    elif command == "toggle_bookmark":
-        toggle_bookmark(*args)
+    elif command == "get_tagged_works":
+        get_tagged_works(*args)
+    elif command == "redownload_image":
+        redownload_image(*args)
     elif command == "toggle_follow":
         toggle_follow(*args)
-    elif command == "validate_timezone":
-        validate_timezone(*args)
+    elif command == "set_bookmark":
+        set_bookmark(*args)
+    elif command == "set_follow":
+        set_follow(*args)
+    elif command == "set_novel_bookmark":
+        set_novel_bookmark(*args)
+    elif command == "get_illust_artist":
+        get_illust_artist(*args)
+    elif command == "set_follow_visibility":
+        set_follow_visibility(*args)
+    elif command == "get_bookmark_detail":
+        get_bookmark_detail(*args)
+    elif command == "save_bookmark":
+        save_bookmark(*args)
+    elif command == "download_bookmarks":
+        download_bookmarks(*args)
+    elif command == "get_recent_works":
+        get_recent_works(*args)
+    elif command == "get_bookmarked_works":
+        get_bookmarked_works(*args)
+    elif command == "mute_artist":
+        mute_artist(*args)
     elif command == "validate_token":
         validate_token(*args)
     else:
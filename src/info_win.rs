@@ -1,7 +1,7 @@
 use druid::commands::CLOSE_WINDOW;
-use druid::widget::{Label, Flex, LineBreaking};
+use druid::widget::{Label, Flex, LineBreaking, Checkbox, TextBox};
 use druid::{WidgetExt, Widget, WindowDesc, EventCtx};
-use std::cmp::Ordering;
+use crate::batch_handler::BatchAction;
 use crate::data::AppData;
 use crate::pixiv_handler::reset_artist_list;
 use crate::settings_win::close_settings_window;
@@ -11,33 +11,48 @@ use crate::ui_globals::ui::{self, create_button};
 /// Info window type
 pub enum InfoWindowType {
     ConfirmTokenChange,
-    ConfirmJump,
+    ConfirmMarkReviewed,
     ConfirmListReload,
+    ConfirmBatch(BatchAction),
+    ConfirmUnfollow,
 }
 
-fn create_info_label(bw: u32, bh: u32, fs: u32, win_type: InfoWindowType) -> impl Widget<AppData> {
-    let label = match win_type {
+fn create_info_label(bw: u32, bh: u32, fs: u32, win_type: &InfoWindowType) -> impl Widget<AppData> {
+    let label = match *win_type {
         InfoWindowType::ConfirmTokenChange => {
             Label::new("You are about to change the token, that will erase all currently downloaded artist data. Are you sure you want to continue?")
         },
-        InfoWindowType::ConfirmJump => {
+        InfoWindowType::ConfirmMarkReviewed => {
             Label::new(|data: &AppData, _: &_| { match data.get_jump_index() {
-                Some(new_index) => match new_index.cmp(&data.artist_index) {
-                    Ordering::Greater => format!("You are about to jump from artist {} to artist {}, that will mark all artists before {} as reviewed. Are you sure you want to continue?", data.artist_index+1, new_index+1, new_index+1),
-                    Ordering::Less => format!("You are about to jump from artist {} to artist {}, that will mark all artists after {} as not reviewed. Are you sure you want to continue?", data.artist_index+1, new_index+1, new_index+1),
-                    Ordering::Equal => "Your jump number is the same as the current artist number. Clicking yes will not do much.".to_string(),
-                },
+                Some(0) => "There are no artists before artist 1. Clicking yes will not do much.".to_string(),
+                Some(new_index) => format!("You are about to mark all artists before artist {} as reviewed. You can undo this in the settings. Are you sure you want to continue?", new_index+1),
                 None => "You broke the jump index, congrats. As a reward, clicking yes will not do anything.".to_string(),
             }})
         },
+        InfoWindowType::ConfirmBatch(action) => {
+            Label::new(move |data: &AppData, _: &_| { match action {
+                BatchAction::BookmarkShown => format!("You are about to bookmark all shown works of {} that are not bookmarked yet. Are you sure you want to continue?", data.artist.name),
+                BatchAction::BookmarkRecent => format!("You are about to bookmark all works of {} from the last {} months. Are you sure you want to continue?", data.artist.name, data.settings.activity_months),
+                BatchAction::RemoveAll => format!("You are about to remove all of your bookmarks of works by {}. This can't be undone. Are you sure you want to continue?", data.artist.name),
+            }})
+        },
+        InfoWindowType::ConfirmUnfollow => {
+            Label::new(|data: &AppData, _: &_| format!("You are about to unfollow {}.", data.artist.name))
+        },
         InfoWindowType::ConfirmListReload => {
             Label::new("You are about to reload the artist list, that will erase all currently downloaded artist data. Are you sure you want to continue?")
         },
     };
 
+    // the unfollow window needs the space for its options
+    let label_height = match win_type {
+        InfoWindowType::ConfirmUnfollow => bh*2,
+        _ => bh*5,
+    };
+
     create_button(
         label.with_line_break_mode(LineBreaking::WordWrap),
-        bw, bh*5, fs, false)
+        bw, label_height, fs, false)
         .on_added(|_, ctx, _, _| {
             unsafe { ui::info_window_id = Some(ctx.window_id()) }
         })
@@ -62,37 +77,67 @@ fn create_yes_button(bw: u32, bh: u32, fs: u32, win_type: InfoWindowType) -> imp
                     data.apply_settings();
                     close_settings_window(ctx, data);
                 },
-                InfoWindowType::ConfirmJump => match data.get_jump_index() {
-                    Some(new_index) => match new_index.cmp(&data.artist_index) {
-                        Ordering::Equal => {},
-                        _ => {
-                            data.jump_to_jump_index();
-                            close_settings_window(ctx, data);
-                        }
-                    },
-                    None => {},
+                InfoWindowType::ConfirmMarkReviewed => {
+                    if data.jump_index_is_valid() {
+                        data.mark_as_reviewed_before_jump_index();
+                    }
+                    close_info_window(ctx, data);
                 },
                 InfoWindowType::ConfirmListReload => {
                     reset_artist_list(data);
                     close_settings_window(ctx, data);
                 },
+                InfoWindowType::ConfirmBatch(action) => {
+                    data.start_batch(ctx.get_external_handle(), action);
+                    close_info_window(ctx, data);
+                },
+                InfoWindowType::ConfirmUnfollow => {
+                    data.unfollow_artist(ctx.get_external_handle());
+                    close_info_window(ctx, data);
+                },
             };
         })
 }
 
+/// Creates the checkboxes and the reason of the unfollow window
+fn create_unfollow_options(bw: u32, bh: u32, fs: u32) -> impl Widget<AppData> {
+    let checkbox_mute = Checkbox::new("Also mute on pixiv")
+        .lens(AppData::unfollow_mute)
+        .fix_size(bw as f64, bh as f64);
+    let checkbox_bookmarks = Checkbox::new("Remove my bookmarks")
+        .lens(AppData::unfollow_remove_bookmarks)
+        .fix_size(bw as f64, bh as f64);
+    let text_reason = TextBox::new()
+        .with_placeholder("reason (only kept in PAR)")
+        .with_font(ui::FONT)
+        .with_text_size((fs-5) as f64)
+        .lens(AppData::unfollow_reason)
+        .fix_size(bw as f64, bh as f64);
+
+    Flex::column()
+        .with_child(checkbox_mute)
+        .with_child(checkbox_bookmarks)
+        .with_child(text_reason)
+}
+
 fn build_ui(win_type: InfoWindowType) -> impl Widget<AppData> {
     let bw = (ui::INFO_WINDOW_WIDTH as u32) - 10;
     let bh = 30;
     let fs = 20;
 
-    let label_info = create_info_label(bw, bh, fs, win_type.clone());
+    let label_info = create_info_label(bw, bh, fs, &win_type);
 
     let button_no = create_no_button(bw, bh, fs);
 
     let button_yes = create_yes_button(bw, bh, fs, win_type.clone());
 
-    Flex::column()
-        .with_child(label_info)
+    let mut cont = Flex::column()
+        .with_child(label_info);
+    if let InfoWindowType::ConfirmUnfollow = win_type {
+        cont.add_child(create_unfollow_options(bw, bh, fs));
+    }
+
+    cont
         .with_spacer(5.0)
         .with_child(
             Flex::row()
@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use druid::commands::CLOSE_WINDOW;
+use druid::widget::{Label, Flex, Scroll, TextBox, ViewSwitcher, Controller};
+use druid::{WidgetExt, Widget, WindowDesc, EventCtx, Event, Env};
+use crate::data::{AppData, ArtistEntry, UserData};
+use crate::date_handler::format_date;
+use crate::file_handler::get_artist_entries;
+use crate::ui_globals::ui::{self, create_button};
+
+/// Columns of the table, as (sort key, header, width in characters)
+const COLUMNS: [(&str, &str, usize); 6] = [
+    ("id", "ID", 9),
+    ("name", "Name", 20),
+    ("state", "State", 9),
+    ("decision", "Decision", 12),
+    ("recent", "Recent", 7),
+    ("last_bookmarked", "Last bookmark", 19),
+];
+/// Width of one character of the table font, used for sizing the header buttons
+const CHAR_WIDTH: u32 = 10;
+
+/// Opens the artist in the main window when its row gets double-clicked
+struct RowController {
+    artist_index: u16,
+}
+
+impl<W: Widget<AppData>> Controller<AppData, W> for RowController {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut AppData, env: &Env) {
+        if let Event::MouseDown(mouse_event) = event {
+            if mouse_event.count == 2 {
+                data.change_artist(self.artist_index, false);
+                close_list_window(ctx, data);
+                return;
+            }
+        }
+        child.event(ctx, event, data, env)
+    }
+}
+
+fn get_name(entry: &ArtistEntry) -> String {
+    entry.summary.as_ref().map(|summary| summary.name.clone()).unwrap_or_else(|| "?".to_string())
+}
+
+fn get_state(entry: &ArtistEntry) -> &'static str {
+    if entry.is_checked { "reviewed" }
+    else { "-" }
+}
+
+/// Returns what the review rules decided about the artist
+fn get_decision(entry: &ArtistEntry) -> String {
+    if entry.rule_matches.is_unfollow_candidate { "unfollow?".to_string() }
+    else if !entry.rule_matches.tags.is_empty() { entry.rule_matches.tags.join(",") }
+    else { "-".to_string() }
+}
+
+fn compare_entries(a: &ArtistEntry, b: &ArtistEntry, sort_column: &str) -> Ordering {
+    match sort_column {
+        "id" => a.id.cmp(&b.id),
+        "name" => get_name(a).to_lowercase().cmp(&get_name(b).to_lowercase()),
+        "state" => a.is_checked.cmp(&b.is_checked),
+        "decision" => get_decision(a).cmp(&get_decision(b)),
+        "recent" => a.summary.as_ref().map(|summary| summary.recent_count).cmp(&b.summary.as_ref().map(|summary| summary.recent_count)),
+        "last_bookmarked" => a.summary.as_ref().and_then(|summary| summary.last_bookmarked).cmp(&b.summary.as_ref().and_then(|summary| summary.last_bookmarked)),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Cuts or pads text to exactly width characters, so that the columns line up in the monospace font
+fn fit_to_width(text: &str, width: usize) -> String {
+    let mut fitted: String = text.chars().take(width - 1).collect();
+    while fitted.chars().count() < width {
+        fitted.push(' ');
+    }
+    fitted
+}
+
+fn format_row(entry: &ArtistEntry, settings: &UserData) -> String {
+    let recent = entry.summary.as_ref().map(|summary| summary.recent_count.to_string()).unwrap_or_else(|| "?".to_string());
+    let last_bookmarked = entry.summary.as_ref().map(|summary| format_date(&summary.last_bookmarked, settings, "never")).unwrap_or_else(|| "?".to_string());
+
+    let values = [entry.id.to_string(), get_name(entry), get_state(entry).to_string(), get_decision(entry), recent, last_bookmarked];
+    values
+        .iter()
+        .zip(COLUMNS.iter())
+        .map(|(value, (_, _, width))| fit_to_width(value, *width))
+        .collect()
+}
+
+fn create_titlebar() -> impl Widget<AppData> {
+    let bw = (ui::LIST_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    create_button(
+        Label::new("Followed artists"),
+        bw, bh, fs, true)
+        .on_added(|_, ctx, _, _| {
+            unsafe { ui::list_window_id = Some(ctx.window_id()) }
+        })
+}
+
+fn create_search_bar() -> impl Widget<AppData> {
+    let bw = (ui::LIST_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    let label_search = create_button(
+        Label::new("Search:"),
+        bw / 4, bh, fs, false);
+    let text_search = TextBox::new()
+        .with_placeholder("name")
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .lens(AppData::list_search)
+        .fix_size((bw - bw / 4) as f64, bh as f64);
+
+    Flex::row()
+        .with_child(label_search)
+        .with_child(text_search)
+}
+
+/// Creates the column headers. Clicking on a header sorts by that column, clicking on it again reverses the order
+fn create_header() -> impl Widget<AppData> {
+    let bh = 30;
+    let fs = 16;
+
+    let mut header = Flex::row();
+    for (sort_column, title, width) in COLUMNS {
+        header.add_child(create_button(
+            Label::new(move |data: &AppData, _: &_| {
+                if data.list_sort != sort_column { title.to_string() }
+                else if data.list_sort_descending { format!("{} v", title) }
+                else { format!("{} ^", title) }
+            }),
+            width as u32 * CHAR_WIDTH, bh, fs, false)
+            .on_click(move |_, data: &mut AppData, _| {
+                if data.list_sort == sort_column {
+                    data.list_sort_descending = !data.list_sort_descending;
+                } else {
+                    data.list_sort = sort_column.to_string();
+                    data.list_sort_descending = false;
+                }
+            }));
+    }
+    header
+}
+
+fn create_rows(data: &AppData) -> Box<dyn Widget<AppData>> {
+    let bw = (ui::LIST_WINDOW_WIDTH as u32) - 10;
+    let bh = 24;
+    let fs = 16;
+
+    let search = data.list_search.trim().to_lowercase();
+    let entries = get_artist_entries();
+    let mut rows: Vec<(u16, &ArtistEntry)> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| (index as u16, entry))
+        .filter(|(_, entry)| search.is_empty() || get_name(entry).to_lowercase().contains(&search))
+        .collect();
+
+    rows.sort_by(|(_, a), (_, b)| compare_entries(a, b, &data.list_sort));
+    if data.list_sort_descending {
+        rows.reverse();
+    }
+
+    let mut cont = Flex::column();
+
+    if rows.is_empty() {
+        cont.add_child(create_button(
+            Label::new("No artists found"),
+            bw, bh, fs, false));
+    }
+
+    for (artist_index, entry) in rows {
+        cont.add_child(create_button(
+            Label::new(format_row(entry, &data.settings)),
+            bw, bh, fs, false)
+            .controller(RowController { artist_index }));
+    }
+
+    Box::new(cont)
+}
+
+fn create_footer() -> impl Widget<AppData> {
+    let bw = (ui::LIST_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    create_button(
+        Label::new("Close"),
+        bw, bh, fs, true)
+        .on_click(|ctx, data, _| {
+            close_list_window(ctx, data);
+        })
+}
+
+fn build_ui() -> impl Widget<AppData> {
+    let titlebar = create_titlebar();
+    let search_bar = create_search_bar();
+    let header = create_header();
+    let footer = create_footer();
+
+    // the rows get rebuilt whenever the search or the sorting changes
+    let rows = ViewSwitcher::new(
+        |data: &AppData, _| (data.list_search.clone(), data.list_sort.clone(), data.list_sort_descending),
+        |_, data: &AppData, _| create_rows(data),
+    );
+
+    let scroll_height = ui::LIST_WINDOW_HEIGHT - 40.0 - 40.0 - 30.0 - 40.0 - 10.0 - 20.0;
+    let body = Scroll::new(rows).vertical().fix_height(scroll_height);
+
+    Flex::column()
+        .with_child(titlebar)
+        .with_spacer(5.0)
+        .with_child(search_bar)
+        .with_spacer(5.0)
+        .with_child(header)
+        .with_child(body)
+        .with_spacer(5.0)
+        .with_child(footer)
+        .padding(5.0)
+}
+
+fn create_list_window() -> WindowDesc<AppData> {
+    WindowDesc::new(build_ui())
+        .window_size((ui::LIST_WINDOW_WIDTH, ui::LIST_WINDOW_HEIGHT))
+        .set_position(unsafe { ui::LIST_WINDOW_POS })
+        .resizable(false)
+        .show_titlebar(false)
+        .title("PAR - Followed artists")
+}
+
+pub fn close_list_window(ctx: &mut EventCtx, data: &mut AppData) {
+    if data.list_window_is_open {
+        unsafe {
+            ctx.submit_command(CLOSE_WINDOW.to(ui::list_window_id.unwrap()));
+            data.list_window_is_open = false;
+            ui::list_window_id = None;
+        }
+    }
+}
+
+pub fn open_list_window(ctx: &mut EventCtx, data: &mut AppData) {
+    close_list_window(ctx, data);
+    ctx.new_window(create_list_window());
+    data.list_window_is_open = true;
+}
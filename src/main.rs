@@ -1,19 +1,40 @@
 use druid::{AppLauncher, theme};
 use ui_globals::ui;
 use main_win::create_main_window;
+use delegate::Delegate;
 use crate::file_handler::{prepare_settings, prepare_artists, prepare_folders};
+use crate::rule_handler::prepare_rules;
 
 mod data;
 mod ui_globals;
 mod main_win;
+mod activity_chart;
 mod settings_win;
 mod info_win;
+mod ranking_win;
+mod list_win;
+mod bookmark_win;
 mod pixiv_handler;
 mod file_handler;
+mod cache_handler;
+mod outbox_handler;
+mod date_handler;
+mod score_handler;
+mod rule_handler;
+mod bookmark_handler;
+mod batch_handler;
+mod unfollow_handler;
+mod content_filter_handler;
+mod scan_handler;
+mod queue_handler;
+mod delegate;
+#[cfg(test)]
+mod test_fixtures;
 
 fn main() {
     println!("Start");
 
+    prepare_rules();
     let mut initial_data = prepare_settings();
     prepare_artists(&mut initial_data);
     prepare_folders();
@@ -22,6 +43,7 @@ fn main() {
     let main_window = create_main_window();
 
     AppLauncher::with_window(main_window)
+        .delegate(Delegate)
         .configure_env(|env, _| {
             env.set(theme::WINDOW_BACKGROUND_COLOR, ui::ACTIVE_COLOR)
         })
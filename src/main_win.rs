@@ -1,10 +1,17 @@
+use std::sync::Arc;
 use druid::{Widget, WidgetExt, ImageBuf, Application, WindowDesc};
-use druid::widget::{Flex, Label, SizedBox, Image, FillStrat, ViewSwitcher, LineBreaking};
+use druid::widget::{Flex, Label, SizedBox, Image, FillStrat, ViewSwitcher, LineBreaking, Either, Scroll};
 use druid::piet::InterpolationMode;
-use crate::data::AppData;
-use crate::file_handler::{get_path_to_pfp, get_path_to_illust};
-use crate::pixiv_handler::{toggle_bookmark, toggle_follow};
+use crate::activity_chart::ActivityChart;
+use crate::data::{AppData, ArtistProfile, TopTag};
+use crate::date_handler::format_date;
+use crate::file_handler::{get_path_to_pfp, get_path_to_illust, get_path_to_background};
+use crate::batch_handler::{is_batch_running, BatchAction};
+use crate::bookmark_win::open_bookmark_window;
+use crate::pixiv_handler::toggle_novel_bookmark;
+use crate::info_win::{open_info_window, InfoWindowType};
 use crate::settings_win::open_settings_window;
+use crate::ranking_win::open_ranking_window;
 use crate::ui_globals::ui::{self, create_button};
 
 /// Creates a widget that displays artist's profile picture
@@ -34,10 +41,37 @@ fn create_illust_widget(illust_index: usize, width: u32, height: u32) -> impl Wi
             if image_option.is_some() {
                 let mut image = Image::new(image_option.as_ref().unwrap().clone()).fill_mode(FillStrat::Cover);
                 image.set_interpolation_mode(InterpolationMode::Bilinear);
-                let image = image.on_click(move |_, data: &mut AppData, _| { open_illust(data.illusts[illust_index].id) });
+                // blurred illustrations get revealed by the first click
+                let image = image.on_click(move |_, data: &mut AppData, _| {
+                    if data.illust_filters[illust_index].is_empty() { open_illust(data.illusts[illust_index].id) }
+                    else { data.reveal_illust(illust_index) }
+                });
                 Box::new(image)
             } else {
-                Box::new(Label::new("Illustration image").with_line_break_mode(LineBreaking::WordWrap).with_font(ui::FONT).with_text_size(20.0).background(ui::HOVER_COLOR))
+                Box::new(Label::new(move |data: &AppData, _: &_| {
+                    if data.illust_filters[illust_index].is_empty() { "Illustration image".to_string() }
+                    else { format!("Hidden ({})", data.illust_filters[illust_index]) }
+                }).with_line_break_mode(LineBreaking::WordWrap).with_font(ui::FONT).with_text_size(20.0).background(ui::HOVER_COLOR))
+            }
+        }
+    );
+
+    SizedBox::new(image_widget)
+        .fix_width(width as f64)
+        .fix_height(height as f64)
+}
+
+/// Creates a widget that displays the background image of artist's profile
+fn create_background_widget(width: u32, height: u32) -> impl Widget<AppData> {
+    let image_widget = ViewSwitcher::new(
+        |data: &AppData, _| data.background_image.clone(), move |image_option: &Option<ImageBuf>, _, _| {
+            if image_option.is_some() {
+                let mut image = Image::new(image_option.as_ref().unwrap().clone()).fill_mode(FillStrat::Cover);
+                image.set_interpolation_mode(InterpolationMode::Bilinear);
+                let image = image.on_click(|_, data: &mut AppData, _| { open_artist_background(data.artist.id) });
+                Box::new(image)
+            } else {
+                Box::new(Label::new("No background image").with_font(ui::FONT).with_text_size(20.0).center().background(ui::HOVER_COLOR))
             }
         }
     );
@@ -57,10 +91,32 @@ fn execute_process(something_to_execute: &str) {
         .expect(&format!("Failed to execute \"{}\"", something_to_execute));
 }
 
+/// Returns true if url is an http or https url that can be safely handed to the browser. Urls from artists' profiles are written by the artists, so they get checked before being opened
+fn is_openable_url(url: &str) -> bool {
+    let rest = match url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")) {
+        Some(r) => r,
+        None => return false,
+    };
+    !rest.is_empty() && !url.chars().any(|c| c.is_whitespace() || c.is_control() || c == '"')
+}
+
+/// Opens an url from an artist's profile in the browser. cmd would run anything after characters like & and |, so the url is passed to the url handler directly instead of through cmd
+fn open_profile_url(url: &str) {
+    if !is_openable_url(url) {
+        return
+    }
+
+    let _ = std::process::Command::new("rundll32").arg("url.dll,FileProtocolHandler").arg(url).spawn();
+}
+
 fn open_artist_pfp(id: u32) {
     execute_process(&get_path_to_pfp(id));
 }
 
+fn open_artist_background(id: u32) {
+    execute_process(&get_path_to_background(id));
+}
+
 fn open_illust(id: u32) {
     execute_process(&get_path_to_illust(id));
 }
@@ -73,6 +129,10 @@ fn open_illust_url(id: u32) {
     execute_process(&format!("https://www.pixiv.net/en/artworks/{}", id));
 }
 
+fn open_novel_url(id: u32) {
+    execute_process(&format!("https://www.pixiv.net/novel/show.php?id={}", id));
+}
+
 /// Creates the titlebar container
 fn create_titlebar() -> Flex<AppData> {
     let bs = 40;  // button size
@@ -87,6 +147,16 @@ fn create_titlebar() -> Flex<AppData> {
         Label::new(|data: &AppData, _: &_| { data.window_title.clone() }),
         bs, bs, fs, true);
 
+    let label_offline = Either::new(
+        |data: &AppData, _| data.is_offline,
+        create_button(
+            Label::new(|data: &AppData, _: &_| {
+                if data.fetched_at.is_none() { "OFFLINE".to_string() }
+                else { format!("OFFLINE, fetched {}", format_date(&data.fetched_at, &data.settings, "")) }
+            }),
+            bs*10, bs, fs-12, true),
+        SizedBox::empty());
+
     //let button_minimize = Button::new("-")
     //    .on_click(|ctx, _, _| { ctx.window().set_window_state(druid::WindowState::Minimized) });
 
@@ -97,18 +167,23 @@ fn create_titlebar() -> Flex<AppData> {
 
     Flex::row()
         .with_child(button_settings)
+        .with_child(label_offline)
         .with_flex_child(label_window_info.expand_width(), 1.0)
         .with_child(button_close)
 }
 
 fn create_artist_container() -> Flex<AppData> {
     let is = 120;  // image size
+    let cw = 100;  // chart width
     let bh = is/2;  // button height
-    let bw = ((ui::WINDOW_WIDTH as u32) - 10 - is) / 2;  // button width
+    let bw = ((ui::WINDOW_WIDTH as u32) - 10 - is - cw) / 2;  // button width
     let fs = 20;
 
     let image_pfp = create_pfp_widget(is, is);
 
+    let chart_activity = ActivityChart::new(cw, is)
+        .lens(AppData::activity);
+
     let label_artist_name = create_button(
         Label::new(|data: &AppData, _: &_| { data.artist.name.clone() }).with_line_break_mode(LineBreaking::WordWrap),
         bw+1, bh, fs+10, false)
@@ -117,13 +192,26 @@ fn create_artist_container() -> Flex<AppData> {
         });
 
     let label_recent_uploads = create_button(
-        Label::new(|data: &AppData, _: &_| { format!("Illustrations in the last 6\nmonths: {}", data.artist.recent_count) }),
+        Label::new(|data: &AppData, _: &_| {
+            if data.artist.counts_novels && data.artist.recent_novel_count > 0 {
+                let months = if data.artist.recent_months == 1 { "month".to_string() } else { format!("{} months", data.artist.recent_months) };
+                format!("Works in the last {}:\n{} ({} novels)", months, data.artist.recent_count, data.artist.recent_novel_count)
+            }
+            else if data.artist.recent_months == 1 { format!("Illustrations in the last\nmonth: {}", data.artist.recent_count) }
+            else { format!("Illustrations in the last {}\nmonths: {}", data.artist.recent_months, data.artist.recent_count) }
+        }),
         bw+1, bh, fs, false);
 
     let label_last_bookmark = create_button(
         Label::new(|data: &AppData, _: &_| {
-            if data.last_bookmarked.id == 0 { format!("No bookmarks in the last {}\nillustrations", data.settings.amount_to_search) }
-            else { format!("Last bookmarked illustration:\n{}", data.last_bookmarked.upload_date) }
+            match data.artist_bookmark_count {
+                Some(0) if data.last_bookmarked.id == 0 => "You have never bookmarked\nthis artist".to_string(),
+                Some(count) if data.last_bookmarked.id != 0 => format!("Last bookmarked ({} total):\n{}", count, format_date(&data.last_bookmarked.upload_date, &data.settings, "unknown date")),
+                _ => {
+                    if data.last_bookmarked.id == 0 { format!("No bookmarks in the last {}\nillustrations", data.settings.amount_to_search) }
+                    else { format!("Last bookmarked illustration:\n{}", format_date(&data.last_bookmarked.upload_date, &data.settings, "unknown date")) }
+                },
+            }
         }), bw, bh, fs, false)
         .on_click(|_, data, _| {
             if data.last_bookmarked.id != 0 { open_illust_url(data.last_bookmarked.id) }
@@ -131,22 +219,181 @@ fn create_artist_container() -> Flex<AppData> {
 
     let label_check_status = create_button(
         Label::new(|data: &AppData, _: &_| {
-            if data.has_been_checked { "You have already reviewed\nthis artist" }
-            else { "You haven't reviewed this\nartist yet" }
+            match data.last_reviewed_at {
+                _ if !data.has_been_checked => "You haven't reviewed this\nartist yet".to_string(),
+                Some(_) if data.new_works_count == 1 => format!("1 new work since last review\n({})", format_date(&data.last_reviewed_at, &data.settings, "")),
+                Some(_) => format!("{} new works since last review\n({})", data.new_works_count, format_date(&data.last_reviewed_at, &data.settings, "")),
+                None => "You have already reviewed\nthis artist".to_string(),
+            }
         }), bw, bh, fs, false);
 
-    Flex::row()
-        .with_child(image_pfp)
-        .with_child(
-            Flex::column()
-                .with_child(label_artist_name)
-                .with_child(label_recent_uploads)
-        )
+    let tag_cloud = ViewSwitcher::new(
+        |data: &AppData, _| data.top_tags.clone(),
+        |top_tags: &Arc<Vec<TopTag>>, _, _| create_tag_cloud(top_tags),
+    );
+
+    Flex::column()
         .with_child(
-            Flex::column()
-                .with_child(label_last_bookmark)
-                .with_child(label_check_status)
+            Flex::row()
+                .with_child(image_pfp)
+                .with_child(chart_activity)
+                .with_child(
+                    Flex::column()
+                        .with_child(label_artist_name)
+                        .with_child(label_recent_uploads)
+                )
+                .with_child(
+                    Flex::column()
+                        .with_child(label_last_bookmark)
+                        .with_child(label_check_status)
+                )
         )
+        .with_child(tag_cloud)
+}
+
+/// Creates a button for each of the artist's most used tags, with bigger text for more used tags. Clicking on a tag shows only the works with that tag, clicking on it again shows all works again
+fn create_tag_cloud(top_tags: &[TopTag]) -> Box<dyn Widget<AppData>> {
+    let width = (ui::WINDOW_WIDTH as u32) - 10;
+    let bh = 30;
+
+    if top_tags.is_empty() {
+        return Box::new(create_button(
+            Label::new("No tags"),
+            width, bh, 16, false));
+    }
+
+    let tw = width / (top_tags.len() as u32);  // tag width
+    let max_count = top_tags.iter().map(|tag| tag.count).max().unwrap_or(1).max(1) as u32;
+
+    let mut cloud = Flex::row();
+    for tag in top_tags {
+        let fs = 14 + 8 * (tag.count as u32) / max_count;
+        let name = tag.name.clone();
+        let text = format!("{} ({})", tag.display_name(), tag.count);
+
+        cloud.add_child(create_button(
+            Label::new({
+                let name = name.clone();
+                move |data: &AppData, _: &_| {
+                    if data.tag_filter == name { format!("[{}]", text) }
+                    else { text.clone() }
+                }
+            }),
+            tw, bh, fs, true)
+            .on_click(move |_, data: &mut AppData, _| {
+                data.toggle_tag_filter(&name);
+            }));
+    }
+
+    Box::new(cloud)
+}
+
+/// Creates the row that shows which review rules the artist matched, the buttons that change what is shown below it, and whether the artist is followed publicly or privately
+fn create_rule_container() -> impl Widget<AppData> {
+    let bh = 30;
+    let vw = 220;  // visibility button width
+    let pw = 140;  // profile button width
+    let nw = 160;  // novels button width
+    let cw = 180;  // card mode button width
+    let bw = (ui::WINDOW_WIDTH as u32) - 10 - vw - pw - nw - cw;
+    let fs = 20;
+
+    let label_rules = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.matched_rules.is_empty() { "No rules matched".to_string() }
+            else { format!("Rules: {}", data.matched_rules) }
+        }),
+        bw, bh, fs, false);
+
+    let button_visibility = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if !data.artist.is_followed { "Not followed".to_string() }
+            else { format!("Followed: {}", data.follow_visibility) }
+        }),
+        vw, bh, fs, true)
+        .on_click(|_, data: &mut AppData, _| {
+            data.toggle_follow_visibility();
+        });
+
+    let button_profile = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.profile_is_expanded { "Profile ^" }
+            else { "Profile v" }
+        }),
+        pw, bh, fs, true)
+        .on_click(|_, data: &mut AppData, _| {
+            data.profile_is_expanded = !data.profile_is_expanded;
+        });
+
+    let button_novels = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.shows_novels { "Illustrations".to_string() }
+            else { format!("Novels ({})", data.artist.recent_novel_count) }
+        }),
+        nw, bh, fs, true)
+        .on_click(|_, data: &mut AppData, _| {
+            data.shows_novels = !data.shows_novels;
+        });
+
+    let button_card_mode = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Cards: {}", data.settings.card_mode) }),
+        cw, bh, fs, true)
+        .on_click(|_, data: &mut AppData, _| {
+            data.cycle_card_mode();
+        });
+
+    Flex::row()
+        .with_child(label_rules)
+        .with_child(button_card_mode)
+        .with_child(button_novels)
+        .with_child(button_profile)
+        .with_child(button_visibility)
+}
+
+/// Creates the row with bookmark actions for all of the artist's works at once
+fn create_batch_container() -> impl Widget<AppData> {
+    let bh = 30;
+    let fs = 20;
+    let status_width = (ui::WINDOW_WIDTH as u32) - 10 - 180 - 280 - 240;
+
+    let button_bookmark_shown = create_button(
+        Label::new("Bookmark shown"),
+        180, bh, fs, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.can_use_pixiv() && data.artist.id != 0 { open_info_window(ctx, data, InfoWindowType::ConfirmBatch(BatchAction::BookmarkShown)) }
+        });
+    let button_bookmark_recent = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.settings.activity_months == "1" { "Bookmark last month".to_string() }
+            else { format!("Bookmark last {} months", data.settings.activity_months) }
+        }),
+        280, bh, fs, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.can_use_pixiv() && data.artist.id != 0 { open_info_window(ctx, data, InfoWindowType::ConfirmBatch(BatchAction::BookmarkRecent)) }
+        });
+    let button_remove_all = create_button(
+        Label::new("Remove my bookmarks"),
+        240, bh, fs, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.can_use_pixiv() && data.artist.id != 0 { open_info_window(ctx, data, InfoWindowType::ConfirmBatch(BatchAction::RemoveAll)) }
+        });
+
+    // clicking on the status while a batch is running cancels it
+    let label_batch_status = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if is_batch_running() { format!("{} (stop)", data.batch_status) }
+            else { data.batch_status.clone() }
+        }),
+        status_width, bh, fs-4, true)
+        .on_click(|_, data: &mut AppData, _| {
+            data.cancel_batch();
+        });
+
+    Flex::row()
+        .with_child(button_bookmark_shown)
+        .with_child(button_bookmark_recent)
+        .with_child(button_remove_all)
+        .with_child(label_batch_status)
 }
 
 fn create_illust(illust_index: usize) -> impl Widget<AppData> {
@@ -164,7 +411,11 @@ fn create_illust(illust_index: usize) -> impl Widget<AppData> {
         is/2, bs, fs, true);
 
     let label_upload_date = create_button(
-        Label::new(move |data: &AppData, _: &_| { data.illusts[illust_index].upload_date.clone() }),
+        Label::new(move |data: &AppData, _: &_| {
+            let upload_date = &data.illusts[illust_index].upload_date;
+            if data.is_new_work(upload_date) { format!("NEW {}", format_date(upload_date, &data.settings, "")) }
+            else { format_date(upload_date, &data.settings, "Upload date") }
+        }),
         is, bs, fs, true);
 
     let button_open = create_button(
@@ -175,11 +426,11 @@ fn create_illust(illust_index: usize) -> impl Widget<AppData> {
         });
     let button_bookmark = create_button(
         Label::new(move |data: &AppData, _: &_| {
-            if data.illusts[illust_index].is_bookmarked { "Unbookmark" }
+            if data.illusts[illust_index].is_bookmarked { "Bookmarked" }
             else { "Bookmark" }
         }), is/2, bs, fs, true)
-        .on_click(move |_, data, _| {
-            if data.illusts[illust_index].id != 0 { toggle_bookmark(data, illust_index) }
+        .on_click(move |ctx, data, _| {
+            if data.illusts[illust_index].id != 0 { open_bookmark_window(ctx, data, illust_index) }
         });
 
     Flex::column()
@@ -197,6 +448,90 @@ fn create_illust(illust_index: usize) -> impl Widget<AppData> {
         )
 }
 
+fn create_novel(novel_index: usize) -> impl Widget<AppData> {
+    let cw = 230;  // card width
+    let th = 200;  // title height
+    let bs = 30;
+    let fs = 20;
+
+    let label_title = create_button(
+        Label::new(move |data: &AppData, _: &_| {
+            match data.novels.get(novel_index) {
+                Some(novel) => novel.title.clone(),
+                None => "No novel".to_string(),
+            }
+        }).with_line_break_mode(LineBreaking::WordWrap),
+        cw, th, fs+4, true)
+        .on_click(move |_, data: &mut AppData, _| {
+            if let Some(novel) = data.novels.get(novel_index) { open_novel_url(novel.id) }
+        });
+
+    let label_length = create_button(
+        Label::new(move |data: &AppData, _: &_| {
+            match data.novels.get(novel_index) {
+                Some(novel) => format!("{} characters", novel.text_length),
+                None => String::new(),
+            }
+        }),
+        cw, bs, fs, true);
+
+    let label_views = create_button(
+        Label::new(move |data: &AppData, _: &_| { format!("V: {}", data.novels.get(novel_index).map_or(0, |novel| novel.views)) }),
+        cw/2, bs, fs, true);
+    let label_bookmarks = create_button(
+        Label::new(move |data: &AppData, _: &_| { format!("B: {}", data.novels.get(novel_index).map_or(0, |novel| novel.bookmarks)) }),
+        cw/2, bs, fs, true);
+
+    let label_upload_date = create_button(
+        Label::new(move |data: &AppData, _: &_| {
+            let upload_date = &data.novels.get(novel_index).and_then(|novel| novel.upload_date);
+            if data.is_new_work(upload_date) { format!("NEW {}", format_date(upload_date, &data.settings, "")) }
+            else { format_date(upload_date, &data.settings, "Upload date") }
+        }),
+        cw, bs, fs, true);
+
+    let button_open = create_button(
+        Label::new("Open"),
+        cw/2, bs, fs, true)
+        .on_click(move |_, data: &mut AppData, _| {
+            if let Some(novel) = data.novels.get(novel_index) { open_novel_url(novel.id) }
+        });
+    let button_bookmark = create_button(
+        Label::new(move |data: &AppData, _: &_| {
+            match data.novels.get(novel_index) {
+                Some(novel) if novel.is_bookmarked => "Bookmarked",
+                _ => "Bookmark",
+            }
+        }), cw/2, bs, fs, true)
+        .on_click(move |_, data: &mut AppData, _| {
+            if novel_index < data.novels.len() { toggle_novel_bookmark(data, novel_index) }
+        });
+
+    Flex::column()
+        .with_child(label_title)
+        .with_child(label_length)
+        .with_child(
+            Flex::row()
+                .with_child(label_views)
+                .with_child(label_bookmarks)
+        )
+        .with_child(label_upload_date)
+        .with_child(
+            Flex::row()
+                .with_child(button_open)
+                .with_child(button_bookmark)
+        )
+}
+
+fn create_novel_container() -> Flex<AppData> {
+    let mut cont = Flex::row();
+    for index in 0..4 {
+        let novel = create_novel(index);
+        cont = cont.with_child(novel).with_spacer(5.0);
+    }
+    cont
+}
+
 fn create_illust_container() -> Flex<AppData> {
     let mut cont = Flex::row();
     for index in 0..4 {
@@ -206,6 +541,103 @@ fn create_illust_container() -> Flex<AppData> {
     cont
 }
 
+/// Creates the rows of profile details. Links get a button each, clicking on one opens it in the browser
+fn create_profile_details(profile: &Option<ArtistProfile>, width: u32) -> Box<dyn Widget<AppData>> {
+    let bh = 30;
+    let fs = 20;
+
+    let profile = match profile {
+        Some(profile) => profile,
+        None => return Box::new(create_button(
+            Label::new("No profile details, reload the artist to download them"),
+            width, bh*3, fs, true)),
+    };
+
+    let mut stats_row = Flex::row();
+    let stats = [
+        format!("Illustrations: {}", profile.total_illusts),
+        format!("Manga: {}", profile.total_manga),
+        format!("Novels: {}", profile.total_novels),
+    ];
+    for stat in stats {
+        stats_row.add_child(create_button(
+            Label::new(stat),
+            width/3, bh, fs, false));
+    }
+
+    let mut info_row = Flex::row()
+        .with_child(create_button(
+            Label::new(if profile.is_premium { "Premium" } else { "Not premium" }),
+            width/4, bh, fs, false))
+        .with_child(create_button(
+            Label::new(if profile.region.is_empty() { "Region: unknown".to_string() } else { format!("Region: {}", profile.region) }),
+            width/4, bh, fs, false));
+
+    let webpage = profile.webpage.clone();
+    info_row.add_child(create_button(
+        Label::new(if webpage.is_empty() { "No webpage".to_string() } else { webpage.clone() }),
+        width - (width/4)*2, bh, fs, false)
+        .on_click(move |_, _, _| {
+            if !webpage.is_empty() { open_profile_url(&webpage) }
+        }));
+
+    let mut links_row = Flex::row();
+    if profile.social_links.is_empty() {
+        links_row.add_child(create_button(
+            Label::new("No social links"),
+            width, bh, fs, false));
+    }
+    let link_width = width / (profile.social_links.len().max(1) as u32);
+    for link in profile.social_links.clone() {
+        links_row.add_child(create_button(
+            Label::new(link.clone()),
+            link_width, bh, fs, false)
+            .on_click(move |_, _, _| { open_profile_url(&link) }));
+    }
+
+    Box::new(
+        Flex::column()
+            .with_child(stats_row)
+            .with_child(info_row)
+            .with_child(links_row)
+    )
+}
+
+/// Creates the panel that is shown in place of the illustrations while the profile is expanded
+fn create_profile_container() -> impl Widget<AppData> {
+    let width = (ui::WINDOW_WIDTH as u32) - 10;
+    let height = 320;  // height of the illustration container
+    let bgh = 100;  // background image height
+    let bh = 30;
+    let fs = 18;
+
+    let image_background = create_background_widget(width, bgh);
+
+    let details = ViewSwitcher::new(
+        |data: &AppData, _| data.profile.clone(),
+        move |profile: &Option<ArtistProfile>, _, _| create_profile_details(profile, width),
+    );
+
+    let label_comment = Label::new(|data: &AppData, _: &_| {
+        match &data.profile {
+            Some(profile) if !profile.comment.is_empty() => profile.comment.clone(),
+            _ => "No bio".to_string(),
+        }})
+        .with_line_break_mode(LineBreaking::WordWrap)
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .padding(5.0);
+    let comment = Scroll::new(label_comment)
+        .vertical()
+        .fix_size(width as f64, (height - bgh - bh*3) as f64)
+        .background(ui::INACTIVE_COLOR);
+
+    Flex::column()
+        .with_child(image_background)
+        .with_child(details)
+        .with_child(comment)
+}
+
 fn create_footer() -> Flex<AppData> {
     let bh = 40;
     let bw = 160;
@@ -216,38 +648,49 @@ fn create_footer() -> Flex<AppData> {
         Label::new("Reload"),
         bw, bh, fs, true)
         .on_click(|_, data, _| {
-            data.change_artist(data.artist_index, true);
+            data.reload_artist();
         });
     let button_follow = create_button(
         Label::new(|data: &AppData, _: &_| {
             if data.artist.is_followed { "Unfollow" }
             else { "Follow" }
         }), bw, bh, fs, true)
-        .on_click(|_, data, _| {
-            toggle_follow(data);
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.artist.is_followed {
+                data.load_unfollow_options();
+                open_info_window(ctx, data, InfoWindowType::ConfirmUnfollow);
+            } else {
+                data.toggle_follow();
+            }
         });
 
-    let label_filler = create_button(
-        Label::new(""),
-        filler_width, bh, fs, true);
+    let button_affinity = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.artist.id == 0 { String::new() }
+            else { format!("Affinity: {:.0}", data.affinity) }
+        }),
+        filler_width, bh, fs, true)
+        .on_click(|ctx, data, _| {
+            open_ranking_window(ctx, data);
+        });
 
     let button_prev = create_button(
         Label::new("<"),
         bw, bh, fs, true)
         .on_click(|_, data, _| {
-            if data.artist_index != 0 { data.change_artist(data.artist_index-1, false) }
+            data.step_artist(false);
         });
     let button_next = create_button(
         Label::new(">"),
         bw, bh, fs, true)
         .on_click(|_, data, _| {
-            data.change_artist(data.artist_index+1, false);
+            data.step_artist(true);
         });
 
     Flex::row()
         .with_child(button_reload)
         .with_child(button_follow)
-        .with_child(label_filler)
+        .with_child(button_affinity)
         .with_child(button_prev)
         .with_child(button_next)
 }
@@ -255,15 +698,33 @@ fn create_footer() -> Flex<AppData> {
 fn build_ui() -> impl Widget<AppData> {
     let titlebar = create_titlebar();
     let artist_container = create_artist_container();
+    let rule_container = create_rule_container();
+    let batch_container = create_batch_container();
     let illust_container = create_illust_container();
+    let novel_container = create_novel_container();
+    let profile_container = create_profile_container();
     let footer = create_footer();
 
+    let body = Either::new(
+        |data: &AppData, _| data.profile_is_expanded,
+        profile_container,
+        Either::new(
+            |data: &AppData, _| data.shows_novels,
+            novel_container,
+            illust_container,
+        ),
+    );
+
     Flex::column()
         .with_child(titlebar)
         .with_spacer(5.0)
         .with_child(artist_container)
         .with_spacer(5.0)
-        .with_child(illust_container)
+        .with_child(rule_container)
+        .with_spacer(5.0)
+        .with_child(batch_container)
+        .with_spacer(5.0)
+        .with_child(body)
         .with_spacer(5.0)
         .with_child(footer)
         .padding(5.0)
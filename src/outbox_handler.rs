@@ -0,0 +1,135 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use druid::{ExtEventSink, Selector, Target};
+use serde::{Deserialize, Serialize};
+use crate::data::UserData;
+use crate::pixiv_handler::{set_bookmark, set_novel_bookmark, set_follow, check_token_validity};
+
+const OUTBOX_PATH: &str = "./outbox.json";
+
+/// How long the reconnect check waits between tries
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Sent by the reconnect check thread once pixiv can be reached again
+pub const PIXIV_REACHABLE: Selector<()> = Selector::new("par.pixiv-reachable");
+
+static RECONNECT_CHECK_RUNNING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+/// Mutation that could not be sent to pixiv
+pub enum OutboxAction {
+    Bookmark {
+        artist_id: u32,
+        illust_id: u32,
+        /// Visibility of the bookmark, either "public" or "private", so that a discarded unbookmark brings it back the same
+        #[serde(default = "default_visibility")]
+        restrict: String,
+    },
+    NovelBookmark { artist_id: u32, novel_id: u32 },
+    Follow {
+        artist_id: u32,
+        /// Visibility to follow the artist with, either "public" or "private"
+        #[serde(default = "default_visibility")]
+        visibility: String,
+    },
+}
+
+fn default_visibility() -> String {
+    "public".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutboxItem {
+    pub action: OutboxAction,
+    /// Whether the illustration or the novel should end up bookmarked or the artist should end up followed
+    pub target_state: bool,
+}
+
+/// Reads outbox.json. Returns an empty outbox if the file is missing or invalid
+pub fn load_outbox() -> Vec<OutboxItem> {
+    std::fs::read_to_string(OUTBOX_PATH).ok()
+        .and_then(|file_cont| serde_json::from_str(&file_cont).ok())
+        .unwrap_or_default()
+}
+
+fn save_outbox(outbox: &[OutboxItem]) {
+    let file_cont = serde_json::to_string(outbox)
+        .expect("Could not convert data to json");
+    std::fs::write(OUTBOX_PATH, file_cont)
+        .expect("Could not write to outbox.json");
+}
+
+/// Adds the mutation to the outbox. If the same bookmark or follow is already waiting, only its target state gets replaced
+pub fn queue_mutation(action: OutboxAction, target_state: bool) {
+    let mut outbox = load_outbox();
+    outbox.retain(|item| item.action != action);
+    outbox.push(OutboxItem { action, target_state });
+    save_outbox(&outbox);
+}
+
+/// Tries to send all pending mutations to pixiv in the order they were made. Stops at the first one that could not reach pixiv and keeps it and everything after it for later. Returns the amount of mutations that are still pending
+pub fn flush_outbox(data: &UserData) -> usize {
+    let mut outbox = load_outbox();
+
+    while let Some(item) = outbox.first() {
+        let result = match &item.action {
+            OutboxAction::Bookmark { illust_id, .. } => set_bookmark(data, *illust_id, item.target_state),
+            OutboxAction::NovelBookmark { novel_id, .. } => set_novel_bookmark(data, *novel_id, item.target_state),
+            OutboxAction::Follow { artist_id, visibility } => set_follow(data, *artist_id, item.target_state, visibility),
+        };
+
+        // if pixiv was reached but did not accept the change, retrying would not help
+        if result.is_none() {
+            break;
+        }
+
+        outbox.remove(0);
+        save_outbox(&outbox);
+    }
+
+    outbox.len()
+}
+
+/// Discards all pending mutations and returns them
+pub fn clear_outbox() -> Vec<OutboxItem> {
+    let outbox = load_outbox();
+    save_outbox(&[]);
+    outbox
+}
+
+/// Returns a human readable list of pending mutations, one per line
+pub fn describe_outbox() -> String {
+    load_outbox()
+        .iter()
+        .map(|item| match &item.action {
+            OutboxAction::Bookmark { illust_id, .. } => format!("{} illustration {}", if item.target_state { "Bookmark" } else { "Unbookmark" }, illust_id),
+            OutboxAction::NovelBookmark { novel_id, .. } => format!("{} novel {}", if item.target_state { "Bookmark" } else { "Unbookmark" }, novel_id),
+            OutboxAction::Follow { artist_id, .. } => format!("{} artist {}", if item.target_state { "Follow" } else { "Unfollow" }, artist_id),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn is_reconnect_check_running() -> bool {
+    RECONNECT_CHECK_RUNNING.load(Ordering::SeqCst)
+}
+
+/// Tries to reach pixiv with the token every RECONNECT_INTERVAL on a separate thread, and sends PIXIV_REACHABLE once it works. Does nothing if a check is already running
+pub fn start_reconnect_check(sink: ExtEventSink, token: String) {
+    if RECONNECT_CHECK_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    thread::spawn(move || {
+        loop {
+            thread::sleep(RECONNECT_INTERVAL);
+            if check_token_validity(&token) {
+                break;
+            }
+        }
+
+        RECONNECT_CHECK_RUNNING.store(false, Ordering::SeqCst);
+        let _ = sink.submit_command(PIXIV_REACHABLE, (), Target::Auto);
+    });
+}
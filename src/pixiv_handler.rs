@@ -1,24 +1,30 @@
 use std::process::Output;
-use crate::data::{UserData, ArtistResult, AppData};
+use serde::Serialize;
+use crate::data::{UserData, ArtistResult, AppData, BookmarkDetail, Illustration};
+use crate::file_handler::{set_cached_novel_bookmark, set_cached_follow};
+use crate::outbox_handler::{queue_mutation, OutboxAction};
+use crate::bookmark_handler::{IndexedBookmark, get_indexed_restrict};
 
 const PYTHON_SCRIPT_PATH: &str = "src/i_give_up.py";
 
-/// Calls the python script with the specified arguments
-fn call_python_script(mut script_args: Vec<&str>) -> Output {
+/// Calls the python script with the specified arguments. Returns None if the script could not be executed or did not succeed, for example when there is no internet connection
+fn try_call_python_script(mut script_args: Vec<&str>) -> Option<Output> {
     // inserting constant args at the beggining of the vector
     script_args.splice(0..0, ["/C", "python", PYTHON_SCRIPT_PATH]);
 
-    let output = std::process::Command::new("cmd")
-        .args(script_args)
-        .output()
-        .expect(&format!("Failed to execute python script"));
+    let output = std::process::Command::new("cmd").args(script_args).output().ok()?;
 
-    let code = output.status.code().unwrap();
-    if code != 0 {
-        panic!("Python script did not succeed, exit code: {}\nstderr:\n{}", code, std::str::from_utf8(&output.stderr).unwrap());
+    if output.status.code() != Some(0) {
+        return None;
     }
 
-    output
+    Some(output)
+}
+
+/// Calls the python script with the specified arguments. Panics if the script did not succeed
+fn call_python_script(script_args: Vec<&str>) -> Output {
+    try_call_python_script(script_args)
+        .expect("Python script did not succeed")
 }
 
 /// Returns parse error message
@@ -26,38 +32,141 @@ fn parse_error(output: &Vec<u8>) -> String {
     format!("Could not parse python output\n{}", std::str::from_utf8(output).unwrap())
 }
 
-/// Downloads artist info from pixiv
-pub fn download_artist_info(data: &UserData, artist_id: u32) -> ArtistResult {
+/// Downloads artist info from pixiv. Returns None if pixiv could not be reached
+pub fn download_artist_info(data: &UserData, artist_id: u32) -> Option<ArtistResult> {
     let artist_id = artist_id.to_string();
     let script_args = vec![
         "download_artist_info",
         &data.token,
         &artist_id,
         &data.amount_to_search,
-        &data.timezone
+        &data.activity_months,
+        &data.activity_works,
+        &data.card_mode,
     ];
-    let output = call_python_script(script_args);
+    let output = try_call_python_script(script_args)?;
 
     let artist_result: ArtistResult = serde_json::from_slice(&output.stdout)
         .expect(&parse_error(&output.stdout));
-    artist_result
+    Some(artist_result)
+}
+
+/// Same as download_artist_info, but doesn't download images or save anything to disk. Returns None if pixiv could not be reached
+pub fn scan_artist_info(data: &UserData, artist_id: u32) -> Option<ArtistResult> {
+    let artist_id = artist_id.to_string();
+    let script_args = vec![
+        "scan_artist_info",
+        &data.token,
+        &artist_id,
+        &data.amount_to_search,
+        &data.activity_months,
+        &data.activity_works,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let artist_result: ArtistResult = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+    Some(artist_result)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Image type that redownload_image can download
+pub enum ImageKind {
+    Pfp,
+    Illust,
+    Background,
+}
+
+/// Downloads a single image again, for example when it was corrupted. Returns true on success, otherwise returns false, including when pixiv could not be reached
+pub fn redownload_image(data: &UserData, kind: ImageKind, id: u32) -> bool {
+    let id = id.to_string();
+    let kind = match kind {
+        ImageKind::Pfp => "pfp",
+        ImageKind::Illust => "illust",
+        ImageKind::Background => "background",
+    };
+    let script_args = vec![
+        "redownload_image",
+        &data.token,
+        kind,
+        &id,
+    ];
+    let output = match try_call_python_script(script_args) {
+        Some(o) => o,
+        None => return false,
+    };
+
+    let success: bool = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+
+    success
+}
+
+/// Makes a followed artist's follow public or private. Returns false if the artist is not followed, or None if pixiv could not be reached
+pub fn set_follow_visibility(data: &UserData, artist_id: u32, visibility: &str) -> Option<bool> {
+    let artist_id = artist_id.to_string();
+    let script_args = vec![
+        "set_follow_visibility",
+        &data.token,
+        &artist_id,
+        visibility,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let success: bool = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+
+    Some(success)
+}
+
+/// Mutes the artist on pixiv, which hides their works from rankings and search. Returns false if pixiv refused, for example because the account can't mute more users, or None if pixiv could not be reached
+pub fn mute_artist(data: &UserData, artist_id: u32) -> Option<bool> {
+    let artist_id = artist_id.to_string();
+    let script_args = vec![
+        "mute_artist",
+        &data.token,
+        &artist_id,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let success: bool = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+
+    Some(success)
+}
+
+/// Which works fill the 4 cards, in the order that the card mode button cycles through them. "recent" are the most recent works, "popular" the most bookmarked ones from the searched range, and "unbookmarked" and "bookmarked" the most recent ones that the user hasn't or has bookmarked
+pub const CARD_MODES: [&str; 4] = ["recent", "popular", "unbookmarked", "bookmarked"];
+
+/// If activity_works is either "all" (illustrations and novels) or "illusts" (only illustrations), returns true, otherwise returns false
+pub fn check_activity_works_validity(activity_works: &str) -> bool {
+    activity_works == "all" || activity_works == "illusts"
+}
+
+/// If follow_list is either "public" (only public follows) or "all" (public and private follows), returns true, otherwise returns false
+pub fn check_follow_list_validity(follow_list: &str) -> bool {
+    follow_list == "public" || follow_list == "all"
 }
 
 pub fn download_artist_list(data: &AppData) {
     let script_args = vec![
         "download_artist_list",
         &data.settings.token,
+        &data.settings.follow_list,
     ];
     let _output = call_python_script(script_args);
 }
 
-/// Tries to create a pixiv client with the token and returns true on success, otherwise returns false
+/// Tries to create a pixiv client with the token and returns true on success, otherwise returns false. Also returns false if pixiv could not be reached
 pub fn check_token_validity(token: &str) -> bool {
     let script_args = vec![
         "validate_token",
         token,
     ];
-    let output = call_python_script(script_args);
+    let output = match try_call_python_script(script_args) {
+        Some(o) => o,
+        None => return false,
+    };
 
     let result: bool = serde_json::from_slice(&output.stdout)
         .expect(&parse_error(&output.stdout));
@@ -65,72 +174,367 @@ pub fn check_token_validity(token: &str) -> bool {
     result
 }
 
-/// If amount_to_search is a valid i16 and is less than 1020, returns true, otherwise returns false
+/// If amount_to_search is a valid u16, returns true, otherwise returns false
 pub fn check_amount_to_search_validity(amount_to_search: &str) -> bool {
-    match amount_to_search.parse::<i16>() {
-        Ok(num) => {
-            if num < 0 || num > 1020 { false }
-            else { true }
-        },
-        Err(_) => false,
+    amount_to_search.parse::<u16>().is_ok()
+}
+
+/// If activity_months is a valid u16 and is in range 1..=120, returns true, otherwise returns false
+pub fn check_activity_months_validity(activity_months: &str) -> bool {
+    matches!(activity_months.parse::<u16>(), Ok(num) if (1..=120).contains(&num))
+}
+
+/// Bookmarks or unbookmarks the illustration, or queues the change while offline. Returns false if pixiv refused it
+pub fn change_bookmark(data: &mut AppData, artist_id: u32, illust_id: u32, target_state: bool) -> bool {
+    if data.requires_initialization && !data.is_offline {
+        return false
+    }
+
+    let restrict = {
+        if target_state { "public".to_string() }
+        else { get_indexed_restrict(artist_id, illust_id).unwrap_or_else(|| "public".to_string()) }
+    };
+    let action = OutboxAction::Bookmark { artist_id, illust_id, restrict };
+
+    if data.is_offline {
+        queue_mutation(action, target_state);
+    } else {
+        // the shown illustrations are not always the ones from the json's "illusts", so the bookmark is changed by id
+        match set_bookmark(&data.settings, illust_id, target_state) {
+            Some(success) => {
+                if success {
+                    data.show_bookmark_change(artist_id, illust_id, target_state, "public");
+                }
+                return success;
+            },
+            None => {
+                data.is_offline = true;
+                queue_mutation(action, target_state);
+            },
+        }
     }
+
+    data.show_bookmark_change(artist_id, illust_id, target_state, "public");
+    data.refresh_pending_changes();
+    true
 }
 
-/// Tries to create a timezone object with the provided timezone string and returns true on success, otherwise returns false
-pub fn check_timezone_validity(timezone: &str) -> bool {
+pub fn toggle_follow(data: &mut AppData) {
+    if data.requires_initialization && !data.is_offline {
+        return
+    }
+
+    let target_state = !data.artist.is_followed;
+    let action = OutboxAction::Follow { artist_id: data.artist.id, visibility: data.follow_visibility.clone() };
+
+    if data.is_offline {
+        queue_mutation(action, target_state);
+    } else {
+        let artist_id = data.artist.id.to_string();
+        let script_args = vec![
+            "toggle_follow",
+            &data.settings.token,
+            &artist_id,
+            &data.follow_visibility,
+        ];
+
+        match try_call_python_script(script_args) {
+            Some(output) => {
+                let success: bool = serde_json::from_slice(&output.stdout)
+                    .expect(&parse_error(&output.stdout));
+
+                if success {
+                    data.artist.is_followed = target_state;
+                }
+                return;
+            },
+            None => {
+                data.is_offline = true;
+                queue_mutation(action, target_state);
+            },
+        }
+    }
+
+    data.artist.is_followed = target_state;
+    set_cached_follow(data.artist.id, target_state);
+    data.refresh_pending_changes();
+}
+
+/// Converts the value to json and hex encodes it. cmd would interpret characters like & and | in tags, so they get passed this way
+fn encode_hex_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value)
+        .expect("Could not convert data to json")
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns up to 4 of the artist's most recent works that have the tag, searching as many works as amount_to_search. Their images get downloaded, but they are not saved to the artist's json. Returns None if pixiv could not be reached
+pub fn get_tagged_works(data: &UserData, artist_id: u32, tag: &str) -> Option<Vec<Illustration>> {
+    let artist_id = artist_id.to_string();
+    let hex_tag = encode_hex_json(&tag);
     let script_args = vec![
-        "validate_timezone",
-        timezone,
+        "get_tagged_works",
+        &data.token,
+        &artist_id,
+        &data.amount_to_search,
+        &hex_tag,
     ];
-    let output = call_python_script(script_args);
+    let output = try_call_python_script(script_args)?;
 
-    let result: bool = serde_json::from_slice(&output.stdout)
+    let illusts: Vec<Illustration> = serde_json::from_slice(&output.stdout)
         .expect(&parse_error(&output.stdout));
 
-    result
+    Some(illusts)
 }
 
-pub fn toggle_bookmark(data: &mut AppData, illust_index: usize) {
-    let artist_id = data.artist.id.to_string();
-    let str_illust_index = illust_index.to_string();
+/// Makes sure that the illustration is (or is not) bookmarked. Returns whether the bookmark ended up in the target state, which is false if the illustration was deleted, or None if pixiv could not be reached
+pub fn set_bookmark(data: &UserData, illust_id: u32, target_state: bool) -> Option<bool> {
+    let illust_id = illust_id.to_string();
+    let target_state = target_state.to_string();
     let script_args = vec![
-        "toggle_bookmark",
-        &data.settings.token,
-        &artist_id,
-        &str_illust_index,
+        "set_bookmark",
+        &data.token,
+        &illust_id,
+        &target_state,
     ];
-    let output = call_python_script(script_args);
+    let output = try_call_python_script(script_args)?;
 
     let success: bool = serde_json::from_slice(&output.stdout)
         .expect(&parse_error(&output.stdout));
 
-    if success {
-        data.illusts[illust_index].is_bookmarked = !data.illusts[illust_index].is_bookmarked;
-    }
+    Some(success)
 }
 
-pub fn toggle_follow(data: &mut AppData) {
-    if data.requires_initialization {
+/// Makes sure that the novel is (or is not) bookmarked. Returns whether the bookmark ended up in the target state, which is false if the novel was deleted, or None if pixiv could not be reached
+pub fn set_novel_bookmark(data: &UserData, novel_id: u32, target_state: bool) -> Option<bool> {
+    let novel_id = novel_id.to_string();
+    let target_state = target_state.to_string();
+    let script_args = vec![
+        "set_novel_bookmark",
+        &data.token,
+        &novel_id,
+        &target_state,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let success: bool = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+
+    Some(success)
+}
+
+/// Same as change_bookmark, but toggles the novel at novel_index
+pub fn toggle_novel_bookmark(data: &mut AppData, novel_index: usize) {
+    if data.requires_initialization && !data.is_offline {
         return
     }
 
-    let artist_id = data.artist.id.to_string();
+    let novel_id = data.novels[novel_index].id;
+    let target_state = !data.novels[novel_index].is_bookmarked;
+    let action = OutboxAction::NovelBookmark { artist_id: data.artist.id, novel_id };
+
+    if data.is_offline {
+        queue_mutation(action, target_state);
+    } else {
+        match set_novel_bookmark(&data.settings, novel_id, target_state) {
+            Some(success) => {
+                if success {
+                    data.novels[novel_index].is_bookmarked = target_state;
+                    set_cached_novel_bookmark(data.artist.id, novel_id, target_state);
+                }
+                return;
+            },
+            None => {
+                data.is_offline = true;
+                queue_mutation(action, target_state);
+            },
+        }
+    }
+
+    data.novels[novel_index].is_bookmarked = target_state;
+    set_cached_novel_bookmark(data.artist.id, novel_id, target_state);
+    data.refresh_pending_changes();
+}
+
+/// Returns the visibility and the tags of the user's bookmark of the illustration. Returns None if pixiv could not be reached
+pub fn get_bookmark_detail(data: &UserData, illust_id: u32) -> Option<BookmarkDetail> {
+    let illust_id = illust_id.to_string();
     let script_args = vec![
-        "toggle_follow",
-        &data.settings.token,
+        "get_bookmark_detail",
+        &data.token,
+        &illust_id,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let bookmark_detail: BookmarkDetail = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+    Some(bookmark_detail)
+}
+
+/// Bookmarks the illustration with the tags and the visibility ("public" or "private"), or changes them if it is already bookmarked. Returns whether the illustration ended up bookmarked, or None if pixiv could not be reached
+pub fn save_bookmark(data: &UserData, illust_id: u32, restrict: &str, tags: &[String]) -> Option<bool> {
+    let illust_id = illust_id.to_string();
+    let hex_tags = encode_hex_json(&tags);
+    let script_args = vec![
+        "save_bookmark",
+        &data.token,
+        &illust_id,
+        restrict,
+        &hex_tags,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let success: bool = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+
+    Some(success)
+}
+
+/// Downloads the user's bookmarks with the restrict ("public" or "private"), most recently bookmarked first, stopping at the bookmark with stop_at_id. 0 downloads all of them. Returns None if pixiv could not be reached
+pub fn download_bookmarks(data: &UserData, restrict: &str, stop_at_id: u32) -> Option<Vec<IndexedBookmark>> {
+    let stop_at_id = stop_at_id.to_string();
+    let script_args = vec![
+        "download_bookmarks",
+        &data.token,
+        restrict,
+        &stop_at_id,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let bookmarks: Vec<IndexedBookmark> = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+    Some(bookmarks)
+}
+
+/// Returns ids of the artist's works uploaded in the last months months that the user hasn't bookmarked yet. Returns None if pixiv could not be reached
+pub fn get_recent_works(data: &UserData, artist_id: u32, months: &str) -> Option<Vec<u32>> {
+    let artist_id = artist_id.to_string();
+    let script_args = vec![
+        "get_recent_works",
+        &data.token,
         &artist_id,
+        months,
     ];
-    let output = call_python_script(script_args);
+    let output = try_call_python_script(script_args)?;
+
+    let illust_ids: Vec<u32> = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+    Some(illust_ids)
+}
+
+/// Returns ids of all of the artist's works that the user has bookmarked. Returns None if pixiv could not be reached
+pub fn get_bookmarked_works(data: &UserData, artist_id: u32) -> Option<Vec<u32>> {
+    let artist_id = artist_id.to_string();
+    let script_args = vec![
+        "get_bookmarked_works",
+        &data.token,
+        &artist_id,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let illust_ids: Vec<u32> = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+    Some(illust_ids)
+}
+
+/// Makes sure that the artist is (or is not) followed. The visibility is only used when following. Returns whether the follow ended up in the target state, which is false if the user was suspended or deleted, or None if pixiv could not be reached
+pub fn set_follow(data: &UserData, artist_id: u32, target_state: bool, visibility: &str) -> Option<bool> {
+    let artist_id = artist_id.to_string();
+    let target_state = target_state.to_string();
+    let script_args = vec![
+        "set_follow",
+        &data.token,
+        &artist_id,
+        &target_state,
+        visibility,
+    ];
+    let output = try_call_python_script(script_args)?;
 
     let success: bool = serde_json::from_slice(&output.stdout)
         .expect(&parse_error(&output.stdout));
 
-    if success {
-        data.artist.is_followed = !data.artist.is_followed;
+    Some(success)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// What an artist lookup input points to
+pub enum LookupTarget {
+    Artist(u32),
+    Illust(u32),
+}
+
+/// Returns the number that directly follows pattern in text
+fn get_number_after(text: &str, pattern: &str) -> Option<u32> {
+    let start = text.find(pattern)? + pattern.len();
+    let digits: String = text[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
+
+/// Parses an artist id, a pixiv user url (like "pixiv.net/users/123" or "member.php?id=123") or an artwork url (like "pixiv.net/artworks/456" or "member_illust.php?illust_id=456"). Returns None if the input is none of those
+pub fn parse_lookup_input(input: &str) -> Option<LookupTarget> {
+    let input = input.trim();
+
+    if let Ok(artist_id) = input.parse::<u32>() {
+        return Some(LookupTarget::Artist(artist_id));
     }
+
+    // illust_id has to be checked before id, since it contains it
+    get_number_after(input, "illust_id=").or_else(|| get_number_after(input, "artworks/"))
+        .map(LookupTarget::Illust)
+        .or_else(|| get_number_after(input, "users/").or_else(|| get_number_after(input, "id=")).map(LookupTarget::Artist))
+}
+
+/// Returns the id of the artist who uploaded the illustration. Returns None if pixiv could not be reached or if the illustration doesn't exist
+pub fn get_illust_artist_id(data: &UserData, illust_id: u32) -> Option<u32> {
+    let illust_id = illust_id.to_string();
+    let script_args = vec![
+        "get_illust_artist",
+        &data.token,
+        &illust_id,
+    ];
+    let output = try_call_python_script(script_args)?;
+
+    let artist_id: Option<u32> = serde_json::from_slice(&output.stdout)
+        .expect(&parse_error(&output.stdout));
+    artist_id
 }
 
 pub fn reset_artist_list(data: &mut AppData) {
     download_artist_list(data);
+    data.forget_review_queue();
     data.change_artist(0, false);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_id_is_an_artist() {
+        assert_eq!(parse_lookup_input("123"), Some(LookupTarget::Artist(123)));
+        assert_eq!(parse_lookup_input("  123 "), Some(LookupTarget::Artist(123)));
+    }
+
+    #[test]
+    fn user_urls_are_artists() {
+        assert_eq!(parse_lookup_input("https://www.pixiv.net/en/users/123"), Some(LookupTarget::Artist(123)));
+        assert_eq!(parse_lookup_input("pixiv.net/users/123/illustrations"), Some(LookupTarget::Artist(123)));
+        assert_eq!(parse_lookup_input("https://www.pixiv.net/member.php?id=123"), Some(LookupTarget::Artist(123)));
+    }
+
+    #[test]
+    fn artwork_urls_are_illustrations() {
+        assert_eq!(parse_lookup_input("https://www.pixiv.net/artworks/456"), Some(LookupTarget::Illust(456)));
+        assert_eq!(parse_lookup_input("https://www.pixiv.net/en/artworks/456#comments"), Some(LookupTarget::Illust(456)));
+        assert_eq!(parse_lookup_input("https://www.pixiv.net/member_illust.php?mode=medium&illust_id=456"), Some(LookupTarget::Illust(456)));
+    }
+
+    #[test]
+    fn anything_else_is_rejected() {
+        assert_eq!(parse_lookup_input(""), None);
+        assert_eq!(parse_lookup_input("artist name"), None);
+        assert_eq!(parse_lookup_input("https://www.pixiv.net/users/"), None);
+        assert_eq!(parse_lookup_input("-5"), None);
+    }
+}
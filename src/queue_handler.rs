@@ -0,0 +1,237 @@
+use std::cmp::Ordering;
+use chrono::{Duration, Utc};
+use crate::data::{ArtistEntry, ArtistSummary};
+use crate::file_handler::{does_path_exist, get_path_to_artist_json};
+use crate::score_handler::calculate_affinity;
+use crate::ui_globals::ui;
+
+/// Orders that the review queue can be sorted by, in the order that the settings button cycles through them
+pub const QUEUE_SORTS: [&str; 6] = ["follow_date", "last_bookmarked", "recent_count", "name", "affinity", "review_status"];
+/// Filters that can be applied to the review queue, in the order that the settings button cycles through them
+pub const QUEUE_FILTERS: [&str; 5] = ["all", "unreviewed", "no_recent_uploads", "unfollow_candidates", "new_works"];
+
+/// Returns the option that comes after current in options. Unknown values start over from the first option
+pub fn get_next_option(options: &[&str], current: &str) -> String {
+    let next = match options.iter().position(|option| *option == current) {
+        Some(position) => (position + 1) % options.len(),
+        None => 0,
+    };
+    options[next].to_string()
+}
+
+pub fn describe_queue_sort(queue_sort: &str) -> &'static str {
+    match queue_sort {
+        "last_bookmarked" => "last bookmark",
+        "recent_count" => "recent works",
+        "name" => "name",
+        "affinity" => "affinity",
+        "review_status" => "review status",
+        _ => "follow date",
+    }
+}
+
+pub fn describe_queue_filter(queue_filter: &str, activity_months: &str) -> String {
+    match queue_filter {
+        "unreviewed" => "unreviewed".to_string(),
+        "no_recent_uploads" if get_months(activity_months) == 1 => "no uploads in the last month".to_string(),
+        "no_recent_uploads" => format!("no uploads in {} months", get_months(activity_months)),
+        "unfollow_candidates" => "unfollow candidates".to_string(),
+        "new_works" => "new works since review".to_string(),
+        _ => "all".to_string(),
+    }
+}
+
+/// Returns activity_months as a number. Invalid values fall back to the default
+fn get_months(activity_months: &str) -> i64 {
+    activity_months.trim().parse()
+        .unwrap_or_else(|_| ui::DEFAULT_ACTIVITY_MONTHS.parse().unwrap())
+}
+
+/// Returns what the user has to do for queue_filter to find anyone. Empty if nothing is needed. Uploads are only known from the last download or scan of each artist, and opening an artist downloads them and reviews them at the same time, so new works since the review only show up after a scan
+pub fn get_queue_filter_note(entries: &[ArtistEntry], queue_filter: &str, activity_months: &str) -> String {
+    if queue_filter == "new_works" && !entries.iter().any(|entry| passes_filter(entry, queue_filter, activity_months)) {
+        "none known, run a scan".to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// The "no_recent_uploads" filter uses the recent activity window, activity_months
+fn passes_filter(entry: &ArtistEntry, queue_filter: &str, activity_months: &str) -> bool {
+    match queue_filter {
+        "unreviewed" => !entry.is_checked,
+        // artists that were never downloaded are skipped, since it's unknown when they last uploaded
+        "no_recent_uploads" => match &entry.summary {
+            Some(summary) => match summary.latest_upload {
+                Some(date) => Utc::now() - date > Duration::days(get_months(activity_months) * 30),
+                None => true,
+            },
+            None => false,
+        },
+        "unfollow_candidates" => entry.rule_matches.is_unfollow_candidate,
+        // artists that were never opened have no review time to compare with, and the latest upload is only as recent as the last download or scan
+        "new_works" => match (&entry.summary, entry.reviewed_at) {
+            (Some(summary), Some(reviewed_at)) => summary.latest_upload.is_some_and(|date| date > reviewed_at),
+            _ => false,
+        },
+        _ => true,
+    }
+}
+
+/// Compares two summaries by queue_sort. Oldest bookmarks, fewest recent works and lowest affinity come first, since those are the artists most worth reviewing
+fn compare_summaries(a: &ArtistSummary, b: &ArtistSummary, queue_sort: &str, score_weights: &str) -> Ordering {
+    match queue_sort {
+        // Option orders None first, so artists that were never bookmarked come before everyone else
+        "last_bookmarked" => a.last_bookmarked.cmp(&b.last_bookmarked),
+        "recent_count" => a.recent_count.cmp(&b.recent_count),
+        "name" => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+        "affinity" => calculate_affinity(a, score_weights).total_cmp(&calculate_affinity(b, score_weights)),
+        _ => Ordering::Equal,
+    }
+}
+
+fn compare_entries(a: &ArtistEntry, b: &ArtistEntry, queue_sort: &str, score_weights: &str) -> Ordering {
+    match queue_sort {
+        "review_status" => a.is_checked.cmp(&b.is_checked),
+        "follow_date" => Ordering::Equal,
+        // artists that were never downloaded have nothing to sort by, so they go last
+        _ => match (&a.summary, &b.summary) {
+            (Some(a), Some(b)) => compare_summaries(a, b, queue_sort, score_weights),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+    }
+}
+
+/// Returns indexes of the artists from artists.json that pass queue_filter, sorted by queue_sort. Ties keep the order of artists.json, which is the order the artists were followed in. If only_cached is true, artists whose info is not on disk are left out. The artist at current_index is always kept, so that the queue can be walked from it even if opening it made it fail the filter
+pub fn get_review_queue(entries: &[ArtistEntry], queue_sort: &str, queue_filter: &str, activity_months: &str, score_weights: &str, only_cached: bool, current_index: u16) -> Vec<u16> {
+    let mut queue: Vec<(u16, &ArtistEntry)> = entries
+        .iter()
+        .enumerate()
+        .filter(|(index, entry)| {
+            *index == current_index as usize
+                || (passes_filter(entry, queue_filter, activity_months) && (!only_cached || does_path_exist(&get_path_to_artist_json(entry.id))))
+        })
+        .map(|(index, entry)| (index as u16, entry))
+        .collect();
+
+    queue.sort_by(|(_, a), (_, b)| compare_entries(a, b, queue_sort, score_weights));
+    queue.into_iter().map(|(index, _)| index).collect()
+}
+
+/// Returns the artist index that comes after (or before, if forward is false) artist_index in the queue. If the artist is not in the queue, for example because it got filtered out, returns the closest artist in the queue by the order of artists.json instead
+pub fn find_next_in_queue(queue: &[u16], artist_index: u16, forward: bool) -> Option<u16> {
+    match queue.iter().position(|index| *index == artist_index) {
+        Some(position) => {
+            if forward { queue.get(position + 1).copied() }
+            else { position.checked_sub(1).map(|position| queue[position]) }
+        },
+        None => {
+            if forward { queue.iter().filter(|index| **index > artist_index).min().copied() }
+            else { queue.iter().filter(|index| **index < artist_index).max().copied() }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::{make_summary, months_ago};
+    use super::*;
+
+    const SCORE_WEIGHTS: &str = "50,30,20";
+    const ACTIVITY_MONTHS: &str = "6";
+
+    #[test]
+    fn unreviewed_filter_checks_review_state() {
+        assert!(passes_filter(&ArtistEntry::new(1, false), "unreviewed", ACTIVITY_MONTHS));
+        assert!(!passes_filter(&ArtistEntry::new(1, true), "unreviewed", ACTIVITY_MONTHS));
+    }
+
+    #[test]
+    fn no_recent_uploads_filter_skips_never_downloaded_artists() {
+        let mut entry = ArtistEntry::new(1, false);
+        assert!(!passes_filter(&entry, "no_recent_uploads", ACTIVITY_MONTHS));
+
+        entry.summary = Some(ArtistSummary { latest_upload: None, ..make_summary() });
+        assert!(passes_filter(&entry, "no_recent_uploads", ACTIVITY_MONTHS));
+
+        entry.summary = Some(ArtistSummary { latest_upload: Some(months_ago(7)), ..make_summary() });
+        assert!(passes_filter(&entry, "no_recent_uploads", ACTIVITY_MONTHS));
+
+        entry.summary = Some(make_summary());
+        assert!(!passes_filter(&entry, "no_recent_uploads", ACTIVITY_MONTHS));
+    }
+
+    #[test]
+    fn no_recent_uploads_filter_uses_the_activity_window() {
+        let entry = ArtistEntry { summary: Some(ArtistSummary { latest_upload: Some(months_ago(7)), ..make_summary() }), ..ArtistEntry::new(1, false) };
+        assert!(passes_filter(&entry, "no_recent_uploads", "6"));
+        assert!(!passes_filter(&entry, "no_recent_uploads", "12"));
+        assert_eq!(describe_queue_filter("no_recent_uploads", "12"), "no uploads in 12 months");
+    }
+
+    #[test]
+    fn new_works_filter_compares_with_review_time() {
+        let mut entry = ArtistEntry { summary: Some(make_summary()), ..ArtistEntry::new(1, true) };
+        assert!(!passes_filter(&entry, "new_works", ACTIVITY_MONTHS));
+
+        entry.reviewed_at = Some(months_ago(2));
+        assert!(passes_filter(&entry, "new_works", ACTIVITY_MONTHS));
+
+        entry.reviewed_at = Some(Utc::now());
+        assert!(!passes_filter(&entry, "new_works", ACTIVITY_MONTHS));
+    }
+
+    #[test]
+    fn new_works_note_asks_for_a_scan_until_new_works_are_known() {
+        let mut entries = vec![ArtistEntry { summary: Some(make_summary()), ..ArtistEntry::new(1, true) }];
+        assert!(!get_queue_filter_note(&entries, "new_works", ACTIVITY_MONTHS).is_empty());
+        assert!(get_queue_filter_note(&entries, "all", ACTIVITY_MONTHS).is_empty());
+
+        entries[0].reviewed_at = Some(months_ago(2));
+        assert!(get_queue_filter_note(&entries, "new_works", ACTIVITY_MONTHS).is_empty());
+    }
+    #[test]
+    fn unknown_filter_passes_everyone() {
+        assert!(passes_filter(&ArtistEntry::new(1, true), "all", ACTIVITY_MONTHS));
+        assert!(passes_filter(&ArtistEntry::new(1, true), "something", ACTIVITY_MONTHS));
+    }
+
+    #[test]
+    fn entries_without_summary_go_last() {
+        let downloaded = ArtistEntry { summary: Some(make_summary()), ..ArtistEntry::new(1, false) };
+        let not_downloaded = ArtistEntry::new(2, false);
+        assert_eq!(compare_entries(&downloaded, &not_downloaded, "recent_count", SCORE_WEIGHTS), Ordering::Less);
+        assert_eq!(compare_entries(&not_downloaded, &downloaded, "recent_count", SCORE_WEIGHTS), Ordering::Greater);
+    }
+
+    #[test]
+    fn entries_compare_by_queue_sort() {
+        let a = ArtistEntry { summary: Some(ArtistSummary { name: "alice".to_string(), recent_count: 5, ..make_summary() }), ..ArtistEntry::new(1, false) };
+        let b = ArtistEntry { summary: Some(ArtistSummary { name: "Bob".to_string(), recent_count: 2, ..make_summary() }), ..ArtistEntry::new(2, true) };
+        assert_eq!(compare_entries(&a, &b, "recent_count", SCORE_WEIGHTS), Ordering::Greater);
+        assert_eq!(compare_entries(&a, &b, "name", SCORE_WEIGHTS), Ordering::Less);
+        assert_eq!(compare_entries(&a, &b, "review_status", SCORE_WEIGHTS), Ordering::Less);
+        assert_eq!(compare_entries(&a, &b, "follow_date", SCORE_WEIGHTS), Ordering::Equal);
+    }
+
+    #[test]
+    fn next_in_queue_follows_the_queue_order() {
+        let queue = [4, 1, 3];
+        assert_eq!(find_next_in_queue(&queue, 4, true), Some(1));
+        assert_eq!(find_next_in_queue(&queue, 1, true), Some(3));
+        assert_eq!(find_next_in_queue(&queue, 3, true), None);
+        assert_eq!(find_next_in_queue(&queue, 3, false), Some(1));
+        assert_eq!(find_next_in_queue(&queue, 4, false), None);
+    }
+
+    #[test]
+    fn next_in_queue_from_outside_of_the_queue_uses_list_order() {
+        let queue = [4, 1, 3];
+        assert_eq!(find_next_in_queue(&queue, 2, true), Some(3));
+        assert_eq!(find_next_in_queue(&queue, 2, false), Some(1));
+        assert_eq!(find_next_in_queue(&queue, 5, true), None);
+        assert_eq!(find_next_in_queue(&[], 0, true), None);
+    }
+}
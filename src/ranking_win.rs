@@ -0,0 +1,109 @@
+use druid::commands::CLOSE_WINDOW;
+use druid::widget::{Label, Flex, Scroll};
+use druid::{WidgetExt, Widget, WindowDesc, EventCtx};
+use crate::data::AppData;
+use crate::file_handler::get_artist_entries;
+use crate::score_handler::get_least_engaged_artists;
+use crate::ui_globals::ui::{self, create_button};
+
+/// Max amount of artists to list
+const RANKING_SIZE: usize = 30;
+
+fn create_titlebar() -> impl Widget<AppData> {
+    let bw = (ui::RANKING_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    create_button(
+        Label::new("Least engaged"),
+        bw, bh, fs, true)
+        .on_added(|_, ctx, _, _| {
+            unsafe { ui::ranking_window_id = Some(ctx.window_id()) }
+        })
+}
+
+fn create_ranking_container(data: &AppData) -> impl Widget<AppData> {
+    let bw = (ui::RANKING_WINDOW_WIDTH as u32) - 10;
+    let bh = 30;
+    let fs = 20;
+
+    let entries = get_artist_entries();
+    let ranking = get_least_engaged_artists(&entries, &data.settings.score_weights, RANKING_SIZE);
+
+    let mut cont = Flex::column();
+
+    if ranking.is_empty() {
+        cont.add_child(create_button(
+            Label::new("No downloaded followed artists yet"),
+            bw, bh, fs, false));
+    }
+
+    for (artist_index, affinity) in ranking {
+        let name = entries[artist_index as usize].summary.as_ref().map(|summary| summary.name.clone()).unwrap_or_default();
+
+        cont.add_child(create_button(
+            Label::new(format!("{:>3.0} {}", affinity, name)),
+            bw, bh, fs, false)
+            .on_click(move |ctx, data: &mut AppData, _| {
+                data.change_artist(artist_index, false);
+                close_ranking_window(ctx, data);
+            }));
+    }
+
+    cont
+}
+
+fn create_footer() -> impl Widget<AppData> {
+    let bw = (ui::RANKING_WINDOW_WIDTH as u32) - 10;
+    let bh = 40;
+    let fs = 30;
+
+    create_button(
+        Label::new("Close"),
+        bw, bh, fs, true)
+        .on_click(|ctx, data, _| {
+            close_ranking_window(ctx, data);
+        })
+}
+
+fn build_ui(data: &AppData) -> impl Widget<AppData> {
+    let titlebar = create_titlebar();
+    let ranking_container = create_ranking_container(data);
+    let footer = create_footer();
+
+    let scroll_height = ui::RANKING_WINDOW_HEIGHT - 40.0 - 40.0 - 10.0 - 10.0;
+    let body = Scroll::new(ranking_container).vertical().fix_height(scroll_height);
+
+    Flex::column()
+        .with_child(titlebar)
+        .with_spacer(5.0)
+        .with_child(body)
+        .with_spacer(5.0)
+        .with_child(footer)
+        .padding(5.0)
+}
+
+fn create_ranking_window(data: &AppData) -> WindowDesc<AppData> {
+    WindowDesc::new(build_ui(data))
+        .window_size((ui::RANKING_WINDOW_WIDTH, ui::RANKING_WINDOW_HEIGHT))
+        .set_position(unsafe { ui::RANKING_WINDOW_POS })
+        .resizable(false)
+        .show_titlebar(false)
+        .title("PAR - Least engaged artists")
+}
+
+pub fn close_ranking_window(ctx: &mut EventCtx, data: &mut AppData) {
+    if data.ranking_window_is_open {
+        unsafe {
+            ctx.submit_command(CLOSE_WINDOW.to(ui::ranking_window_id.unwrap()));
+            data.ranking_window_is_open = false;
+            ui::ranking_window_id = None;
+        }
+    }
+}
+
+pub fn open_ranking_window(ctx: &mut EventCtx, data: &mut AppData) {
+    close_ranking_window(ctx, data);
+    ctx.new_window(create_ranking_window(data));
+    data.ranking_window_is_open = true;
+}
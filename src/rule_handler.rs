@@ -0,0 +1,256 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::data::ArtistSummary;
+use crate::file_handler::{does_path_exist, get_artist_entries, save_artist_entries};
+use crate::score_handler::calculate_affinity;
+
+const RULES_PATH: &str = "./rules.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+/// Artist value that a rule condition can check
+pub enum RuleField {
+    RecentCount,
+    MonthsSinceLastUpload,
+    MonthsSinceLastBookmark,
+    WorksSinceLastBookmark,
+    SearchedCount,
+    BookmarkedCount,
+    Affinity,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum RuleOperator {
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = "<=")]
+    LessOrEqual,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = ">=")]
+    GreaterOrEqual,
+    #[serde(rename = "==")]
+    Equal,
+    #[serde(rename = "!=")]
+    NotEqual,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RuleCondition {
+    pub field: RuleField,
+    pub op: RuleOperator,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Review rule from rules.json. A rule matches when all of its conditions are true
+pub struct Rule {
+    pub name: String,
+    pub conditions: Vec<RuleCondition>,
+    /// Tag to give to the artist when the rule matches
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// Whether to add the artist to the unfollow candidates when the rule matches
+    #[serde(default)]
+    pub unfollow_candidate: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+/// What the review rules decided about an artist
+pub struct RuleMatches {
+    pub matched_rules: Vec<String>,
+    pub tags: Vec<String>,
+    pub is_unfollow_candidate: bool,
+}
+
+fn get_default_rules() -> Vec<Rule> {
+    vec![
+        Rule {
+            name: "No uploads in 12 months".to_string(),
+            conditions: vec![
+                RuleCondition { field: RuleField::MonthsSinceLastUpload, op: RuleOperator::GreaterOrEqual, value: 12.0 },
+            ],
+            tag: Some("inactive".to_string()),
+            unfollow_candidate: true,
+        },
+        Rule {
+            name: "No bookmarks in last 300 works".to_string(),
+            conditions: vec![
+                RuleCondition { field: RuleField::WorksSinceLastBookmark, op: RuleOperator::GreaterOrEqual, value: 300.0 },
+            ],
+            tag: Some("unengaged".to_string()),
+            unfollow_candidate: false,
+        },
+        Rule {
+            name: "Quiet and forgotten".to_string(),
+            conditions: vec![
+                RuleCondition { field: RuleField::RecentCount, op: RuleOperator::Equal, value: 0.0 },
+                RuleCondition { field: RuleField::MonthsSinceLastBookmark, op: RuleOperator::Greater, value: 24.0 },
+            ],
+            tag: None,
+            unfollow_candidate: true,
+        },
+    ]
+}
+
+/// Checks if rules.json exists. Creates it with the default rules if it doesn't
+pub fn prepare_rules() {
+    if !does_path_exist(RULES_PATH) {
+        let file_cont = serde_json::to_string_pretty(&get_default_rules())
+            .expect("Could not convert data to json");
+        std::fs::write(RULES_PATH, file_cont)
+            .expect("Could not write to rules.json");
+    }
+}
+
+/// Reads rules.json. Returns no rules if the file is missing. Returns the reason if the file is invalid
+fn load_rules() -> Result<Vec<Rule>, String> {
+    let file_cont = match std::fs::read_to_string(RULES_PATH) {
+        Ok(fc) => fc,
+        Err(_) => return Ok(Vec::new()),
+    };
+
+    serde_json::from_str(&file_cont).map_err(|e| format!("rules.json is invalid ({})", e))
+}
+
+/// Returns why rules.json was rejected. Returns None if it can be used
+pub fn get_rules_error() -> Option<String> {
+    load_rules().err()
+}
+
+/// Returns months passed since the date. Returns infinity if there is no date, so that "older than" conditions match artists that have nothing at all
+fn get_months_since(date: &Option<DateTime<Utc>>) -> f64 {
+    match date {
+        Some(date) => (Utc::now() - *date).num_days().max(0) as f64 / 30.0,
+        None => f64::INFINITY,
+    }
+}
+
+fn get_field_value(field: RuleField, summary: &ArtistSummary, score_weights: &str) -> f64 {
+    match field {
+        RuleField::RecentCount => summary.recent_count as f64,
+        RuleField::MonthsSinceLastUpload => get_months_since(&summary.latest_upload),
+        RuleField::MonthsSinceLastBookmark => get_months_since(&summary.last_bookmarked),
+        // an unknown position means that the json was saved by an older version of the app, so the condition shouldn't match
+        RuleField::WorksSinceLastBookmark => summary.works_since_last_bookmark.map(|works| works as f64).unwrap_or(0.0),
+        RuleField::SearchedCount => summary.searched_count as f64,
+        RuleField::BookmarkedCount => summary.bookmarked_count as f64,
+        RuleField::Affinity => calculate_affinity(summary, score_weights),
+    }
+}
+
+fn is_condition_true(condition: &RuleCondition, summary: &ArtistSummary, score_weights: &str) -> bool {
+    let value = get_field_value(condition.field, summary, score_weights);
+    match condition.op {
+        RuleOperator::Less => value < condition.value,
+        RuleOperator::LessOrEqual => value <= condition.value,
+        RuleOperator::Greater => value > condition.value,
+        RuleOperator::GreaterOrEqual => value >= condition.value,
+        RuleOperator::Equal => value == condition.value,
+        RuleOperator::NotEqual => value != condition.value,
+    }
+}
+
+fn apply_rules(rules: &[Rule], summary: &ArtistSummary, score_weights: &str) -> RuleMatches {
+    let mut rule_matches = RuleMatches::default();
+
+    for rule in rules {
+        if !rule.conditions.iter().all(|condition| is_condition_true(condition, summary, score_weights)) {
+            continue;
+        }
+
+        if let Some(tag) = &rule.tag {
+            if !rule_matches.tags.contains(tag) {
+                rule_matches.tags.push(tag.clone());
+            }
+        }
+        rule_matches.is_unfollow_candidate |= rule.unfollow_candidate;
+        rule_matches.matched_rules.push(rule.name.clone());
+    }
+
+    rule_matches
+}
+
+/// Evaluates all rules from rules.json against the artist. An invalid rules.json matches nothing
+pub fn evaluate_rules(summary: &ArtistSummary, score_weights: &str) -> RuleMatches {
+    apply_rules(&load_rules().unwrap_or_default(), summary, score_weights)
+}
+
+/// Evaluates the rules again for every artist that has a summary, for example after rules.json was edited. Returns the amount of unfollow candidates, or why rules.json was rejected, in which case nothing gets changed
+pub fn reevaluate_all_rules(score_weights: &str) -> Result<usize, String> {
+    let rules = load_rules()?;
+    let mut entries = get_artist_entries();
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    for entry in entries.iter_mut() {
+        if let Some(summary) = &entry.summary {
+            entry.rule_matches = apply_rules(&rules, summary, score_weights);
+        }
+    }
+
+    save_artist_entries(&entries);
+    Ok(entries.iter().filter(|entry| entry.rule_matches.is_unfollow_candidate).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_fixtures::{make_summary, months_ago};
+    use super::*;
+
+    const SCORE_WEIGHTS: &str = "50,30,20";
+
+    #[test]
+    fn active_artist_matches_no_default_rule() {
+        let rule_matches = apply_rules(&get_default_rules(), &make_summary(), SCORE_WEIGHTS);
+        assert!(rule_matches.matched_rules.is_empty());
+        assert!(rule_matches.tags.is_empty());
+        assert!(!rule_matches.is_unfollow_candidate);
+    }
+
+    #[test]
+    fn inactive_artist_becomes_unfollow_candidate() {
+        let summary = ArtistSummary { recent_count: 0, latest_upload: Some(months_ago(13)), ..make_summary() };
+        let rule_matches = apply_rules(&get_default_rules(), &summary, SCORE_WEIGHTS);
+        assert_eq!(rule_matches.matched_rules, vec!["No uploads in 12 months".to_string()]);
+        assert_eq!(rule_matches.tags, vec!["inactive".to_string()]);
+        assert!(rule_matches.is_unfollow_candidate);
+    }
+
+    #[test]
+    fn all_conditions_have_to_be_true() {
+        // no recent works, but bookmarked recently
+        let mut summary = ArtistSummary { recent_count: 0, latest_upload: Some(months_ago(2)), last_bookmarked: Some(months_ago(2)), ..make_summary() };
+        assert!(apply_rules(&get_default_rules(), &summary, SCORE_WEIGHTS).matched_rules.is_empty());
+
+        // never bookmarked counts as bookmarked infinitely long ago
+        summary.last_bookmarked = None;
+        let rule_matches = apply_rules(&get_default_rules(), &summary, SCORE_WEIGHTS);
+        assert_eq!(rule_matches.matched_rules, vec!["Quiet and forgotten".to_string()]);
+        assert!(rule_matches.is_unfollow_candidate);
+    }
+
+    #[test]
+    fn tags_are_not_repeated() {
+        let rule = Rule {
+            name: "Any".to_string(),
+            conditions: Vec::new(),
+            tag: Some("tag".to_string()),
+            unfollow_candidate: false,
+        };
+        let rule_matches = apply_rules(&[rule.clone(), rule], &make_summary(), SCORE_WEIGHTS);
+        assert_eq!(rule_matches.matched_rules.len(), 2);
+        assert_eq!(rule_matches.tags, vec!["tag".to_string()]);
+    }
+
+    #[test]
+    fn unknown_works_since_last_bookmark_does_not_match() {
+        let condition = RuleCondition { field: RuleField::WorksSinceLastBookmark, op: RuleOperator::GreaterOrEqual, value: 300.0 };
+        let mut summary = ArtistSummary { works_since_last_bookmark: None, ..make_summary() };
+        assert!(!is_condition_true(&condition, &summary, SCORE_WEIGHTS));
+
+        summary.works_since_last_bookmark = Some(300);
+        assert!(is_condition_true(&condition, &summary, SCORE_WEIGHTS));
+    }
+}
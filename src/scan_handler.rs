@@ -0,0 +1,168 @@
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::thread;
+use std::time::Duration;
+use druid::{ExtEventSink, Selector, Target};
+use serde::{Deserialize, Serialize};
+use crate::data::{ArtistResult, UserData};
+use crate::file_handler::{does_path_exist, get_artist_entries};
+use crate::pixiv_handler::scan_artist_info;
+
+const SCAN_PATH: &str = "./scan.json";
+
+const SCAN_IDLE: u8 = 0;
+const SCAN_RUNNING: u8 = 1;
+const SCAN_PAUSED: u8 = 2;
+const SCAN_CANCELLED: u8 = 3;
+
+/// State of the scan thread, one of the SCAN_ constants
+static SCAN_STATE: AtomicU8 = AtomicU8::new(SCAN_IDLE);
+/// Set when a new scan has to wait for the previous scan thread to stop
+static SCAN_RESTART_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Sent by the scan thread after every scanned artist
+pub const SCAN_UPDATE: Selector<ScanUpdate> = Selector::new("par.scan-update");
+/// Sent by the scan thread when it stops, true if the whole list was scanned
+pub const SCAN_STOPPED: Selector<bool> = Selector::new("par.scan-stopped");
+
+pub struct ScanUpdate {
+    pub artist_index: u16,
+    pub artist_id: u32,
+    /// None if pixiv could not be reached
+    pub artist_result: Option<ArtistResult>,
+    pub total_artists: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// Struct used for saving scan progress
+pub struct ScanProgress {
+    pub next_index: u16,
+    pub total_artists: u16,
+}
+
+pub fn load_scan_progress() -> Option<ScanProgress> {
+    let file_cont = std::fs::read_to_string(SCAN_PATH).ok()?;
+    serde_json::from_str(&file_cont).ok()
+}
+
+pub fn save_scan_progress(progress: &ScanProgress) {
+    let file_cont = serde_json::to_string(progress)
+        .expect("Could not convert data to json");
+    std::fs::write(SCAN_PATH, file_cont)
+        .expect("Could not write to scan.json");
+}
+
+pub fn remove_scan_progress() {
+    if does_path_exist(SCAN_PATH) {
+        let _ = std::fs::remove_file(SCAN_PATH);
+    }
+}
+
+/// Returns true if the scan thread is running or paused
+pub fn is_scan_active() -> bool {
+    matches!(SCAN_STATE.load(Ordering::SeqCst), SCAN_RUNNING | SCAN_PAUSED)
+}
+
+pub fn is_scan_paused() -> bool {
+    SCAN_STATE.load(Ordering::SeqCst) == SCAN_PAUSED
+}
+
+/// Returns true if there is a paused scan or saved scan progress
+pub fn can_resume_scan() -> bool {
+    match SCAN_STATE.load(Ordering::SeqCst) {
+        SCAN_PAUSED => true,
+        SCAN_IDLE => load_scan_progress().is_some(),
+        _ => false,
+    }
+}
+
+/// Scans the artists from start_index onwards until cancelled or pixiv can't be reached
+fn run_scan(sink: ExtEventSink, data: UserData, artist_ids: Vec<u32>, start_index: u16) {
+    let total_artists = artist_ids.len() as u16;
+    let mut finished = true;
+
+    for (index, artist_id) in artist_ids.into_iter().enumerate().skip(start_index as usize) {
+        while SCAN_STATE.load(Ordering::SeqCst) == SCAN_PAUSED {
+            thread::sleep(Duration::from_millis(200));
+        }
+
+        if SCAN_STATE.load(Ordering::SeqCst) == SCAN_CANCELLED {
+            finished = false;
+            break;
+        }
+
+        let artist_result = scan_artist_info(&data, artist_id);
+        let reached_pixiv = artist_result.is_some();
+
+        let update = ScanUpdate {
+            artist_index: index as u16,
+            artist_id,
+            artist_result,
+            total_artists,
+        };
+        if sink.submit_command(SCAN_UPDATE, update, Target::Auto).is_err() {
+            // the app was closed
+            finished = false;
+            break;
+        }
+
+        if !reached_pixiv {
+            finished = false;
+            break;
+        }
+    }
+
+    // the state gets reset by mark_scan_stopped once all updates have been applied
+    if sink.submit_command(SCAN_STOPPED, finished, Target::Auto).is_err() {
+        SCAN_STATE.store(SCAN_IDLE, Ordering::SeqCst);
+    }
+}
+
+/// Should be called when SCAN_STOPPED is received. Returns true if a waiting scan should be started now
+pub fn mark_scan_stopped() -> bool {
+    SCAN_STATE.store(SCAN_IDLE, Ordering::SeqCst);
+    SCAN_RESTART_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// Starts a new scan or resumes a paused one. Returns true if a scan is running afterwards
+pub fn start_scan(sink: ExtEventSink, data: UserData, from_beginning: bool) -> bool {
+    match SCAN_STATE.load(Ordering::SeqCst) {
+        SCAN_IDLE => {
+            let start_index = {
+                if from_beginning { 0 }
+                else { load_scan_progress().map(|progress| progress.next_index).unwrap_or(0) }
+            };
+
+            // reading the list here, since the thread reading it while the app writes to it could get a half written file
+            let artist_ids: Vec<u32> = get_artist_entries().iter().map(|entry| entry.id).collect();
+
+            SCAN_STATE.store(SCAN_RUNNING, Ordering::SeqCst);
+            thread::spawn(move || run_scan(sink, data, artist_ids, start_index));
+            true
+        },
+        _ if from_beginning => {
+            SCAN_RESTART_REQUESTED.store(true, Ordering::SeqCst);
+            SCAN_STATE.store(SCAN_CANCELLED, Ordering::SeqCst);
+            false
+        },
+        SCAN_PAUSED | SCAN_RUNNING => {
+            SCAN_STATE.store(SCAN_RUNNING, Ordering::SeqCst);
+            true
+        },
+        // the cancelled scan is still stopping and its progress is gone
+        _ => false,
+    }
+}
+
+pub fn pause_scan() {
+    let _ = SCAN_STATE.compare_exchange(SCAN_RUNNING, SCAN_PAUSED, Ordering::SeqCst, Ordering::SeqCst);
+}
+
+/// Stops the scan and forgets its progress
+pub fn cancel_scan() {
+    SCAN_RESTART_REQUESTED.store(false, Ordering::SeqCst);
+    let state = SCAN_STATE.load(Ordering::SeqCst);
+    if state == SCAN_RUNNING || state == SCAN_PAUSED {
+        SCAN_STATE.store(SCAN_CANCELLED, Ordering::SeqCst);
+    }
+    remove_scan_progress();
+}
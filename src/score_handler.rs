@@ -0,0 +1,71 @@
+use chrono::Utc;
+use crate::data::{ArtistEntry, ArtistSummary};
+use crate::ui_globals::ui;
+
+/// Parses score weights formatted as "recency,frequency,recent works". Returns None if there are not exactly 3 non-negative numbers or if all of them are 0
+fn parse_score_weights(score_weights: &str) -> Option<[f64; 3]> {
+    let weights: Vec<f64> = score_weights
+        .split(',')
+        .map(|weight| weight.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+
+    if weights.len() != 3 || weights.iter().any(|weight| !weight.is_finite() || *weight < 0.0) || weights.iter().sum::<f64>() == 0.0 {
+        return None;
+    }
+
+    Some([weights[0], weights[1], weights[2]])
+}
+
+/// If score_weights can be parsed by parse_score_weights, returns true, otherwise returns false
+pub fn check_score_weights_validity(score_weights: &str) -> bool {
+    parse_score_weights(score_weights).is_some()
+}
+
+/// Calculates how engaged the user is with the artist, from 0 (never bookmarks anything) to 100. It combines how recently the user bookmarked the artist's works, what part of the artist's searched works the user bookmarked, and what part of the shown recent works the user bookmarked
+pub fn calculate_affinity(summary: &ArtistSummary, score_weights: &str) -> f64 {
+    let [recency_weight, frequency_weight, recent_works_weight] = parse_score_weights(score_weights)
+        .or_else(|| parse_score_weights(ui::DEFAULT_SCORE_WEIGHTS))
+        .unwrap();
+
+    // halves every 6 months since the last bookmarked upload
+    let recency = match summary.last_bookmarked {
+        Some(date) => {
+            let months = (Utc::now() - date).num_days().max(0) as f64 / 30.0;
+            0.5_f64.powf(months / 6.0)
+        },
+        None => 0.0,
+    };
+
+    let frequency = {
+        if summary.searched_count == 0 { 0.0 }
+        else { summary.bookmarked_count as f64 / summary.searched_count as f64 }
+    };
+
+    let recent_works = {
+        if summary.shown_count == 0 { 0.0 }
+        else { summary.shown_bookmarked as f64 / summary.shown_count as f64 }
+    };
+
+    let total_weight = recency_weight + frequency_weight + recent_works_weight;
+    100.0 * (recency * recency_weight + frequency * frequency_weight + recent_works * recent_works_weight) / total_weight
+}
+
+/// Returns up to count indexes of followed artists with the lowest affinity, lowest first. Artists that were never downloaded are skipped, since there is nothing to score them by
+pub fn get_least_engaged_artists(entries: &[ArtistEntry], score_weights: &str, count: usize) -> Vec<(u16, f64)> {
+    let mut scored: Vec<(u16, f64)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let summary = entry.summary.as_ref()?;
+            if !summary.is_followed {
+                return None;
+            }
+            Some((index as u16, calculate_affinity(summary, score_weights)))
+        })
+        .collect();
+
+    scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+    scored.truncate(count);
+    scored
+}
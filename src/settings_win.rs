@@ -1,7 +1,11 @@
 use druid::commands::CLOSE_WINDOW;
-use druid::widget::{Label, Flex, TextBox};
+use druid::widget::{Label, Flex, TextBox, Scroll, LineBreaking, Either, SizedBox};
 use druid::{WidgetExt, Widget, WindowDesc, EventCtx};
 use crate::data::AppData;
+use crate::scan_handler::is_scan_active;
+use crate::queue_handler::{describe_queue_sort, describe_queue_filter, get_next_option};
+use crate::content_filter_handler::{describe_x_restrict_filter, X_RESTRICT_FILTERS};
+use crate::list_win::open_list_window;
 use crate::info_win::{open_info_window, close_info_window, InfoWindowType};
 use crate::ui_globals::ui::{self, create_button};
 
@@ -55,13 +59,145 @@ fn create_settings_container(data: &AppData) -> impl Widget<AppData> {
         .lens(AppData::temp_timezone)
         .fix_size(bw as f64, bh as f64);
 
+    let label_max_cache_size = create_button(
+        Label::new("Max cache size (MB):"),
+        bw, bh, fs, false);
+    let text_max_cache_size = TextBox::new()
+        .with_placeholder(data.temp_max_cache_size.clone())
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .lens(AppData::temp_max_cache_size)
+        .fix_size(bw as f64, bh as f64);
+
+    let label_max_cached_artists = create_button(
+        Label::new("Max cached artists:"),
+        bw, bh, fs, false);
+    let text_max_cached_artists = TextBox::new()
+        .with_placeholder(data.temp_max_cached_artists.clone())
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .lens(AppData::temp_max_cached_artists)
+        .fix_size(bw as f64, bh as f64);
+
+    let label_activity_months = create_button(
+        Label::new("Activity (months):"),
+        bw, bh, fs, false);
+    let text_activity_months = TextBox::new()
+        .with_placeholder(data.temp_activity_months.clone())
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .lens(AppData::temp_activity_months)
+        .fix_size(bw as f64, bh as f64);
+
+    let label_score_weights = create_button(
+        Label::new("Affinity weights:"),
+        bw, bh, fs, false);
+    let text_score_weights = TextBox::new()
+        .with_placeholder(data.temp_score_weights.clone())
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .lens(AppData::temp_score_weights)
+        .fix_size(bw as f64, bh as f64);
+
+    let button_date_format = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Dates: {}", data.temp_date_format) }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.temp_date_format = {
+                if data.temp_date_format == "relative" { "absolute".to_string() }
+                else { "relative".to_string() }
+            };
+        });
+
+    let button_follow_list = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.temp_follow_list == "all" { "Follows: public+private" }
+            else { "Follows: public only" }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.temp_follow_list = {
+                if data.temp_follow_list == "all" { "public".to_string() }
+                else { "all".to_string() }
+            };
+        });
+
+    let button_activity_works = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.temp_activity_works == "illusts" { "Activity: illusts only" }
+            else { "Activity: illusts+novels" }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.temp_activity_works = {
+                if data.temp_activity_works == "illusts" { "all".to_string() }
+                else { "illusts".to_string() }
+            };
+        });
+
+    let button_x_restrict_filter = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Age filter: {}", describe_x_restrict_filter(&data.temp_x_restrict_filter)) }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.temp_x_restrict_filter = get_next_option(&X_RESTRICT_FILTERS, &data.temp_x_restrict_filter);
+        });
+
+    let button_ai_filter = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.temp_ai_filter == "filter" { "AI works: filtered" }
+            else { "AI works: shown" }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.temp_ai_filter = {
+                if data.temp_ai_filter == "filter" { "show".to_string() }
+                else { "filter".to_string() }
+            };
+        });
+
+    let label_tag_blacklist = create_button(
+        Label::new("Filtered tags:"),
+        bw, bh, fs, false);
+    let text_tag_blacklist = TextBox::new()
+        .with_placeholder("separated by spaces")
+        .with_font(ui::FONT)
+        .with_text_size(fs as f64)
+        .lens(AppData::temp_tag_blacklist)
+        .fix_size(bw as f64, bh as f64);
+
+    let button_filter_mode = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Filtered works: {}", data.temp_filter_mode) }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.temp_filter_mode = {
+                if data.temp_filter_mode == "hide" { "blur".to_string() }
+                else { "hide".to_string() }
+            };
+        });
+
     Flex::column()
         .with_child(label_token)
         .with_child(text_token)
         .with_child(label_amount_to_search)
         .with_child(text_amount_to_search)
+        .with_child(label_activity_months)
+        .with_child(text_activity_months)
+        .with_child(button_activity_works)
+        .with_child(label_score_weights)
+        .with_child(text_score_weights)
         .with_child(label_timezone)
         .with_child(text_timezone)
+        .with_child(button_date_format)
+        .with_child(button_follow_list)
+        .with_child(button_x_restrict_filter)
+        .with_child(button_ai_filter)
+        .with_child(label_tag_blacklist)
+        .with_child(text_tag_blacklist)
+        .with_child(button_filter_mode)
+        .with_child(label_max_cache_size)
+        .with_child(text_max_cache_size)
+        .with_child(label_max_cached_artists)
+        .with_child(text_max_cached_artists)
 }
 
 fn create_function_container(data: &AppData) -> impl Widget<AppData> {
@@ -76,7 +212,10 @@ fn create_function_container(data: &AppData) -> impl Widget<AppData> {
         })}),
         bw - (bw / 5), bh, fs, false)
         .on_click(|ctx, data, _| {
-            if data.jump_index_is_valid() { open_info_window(ctx, data, InfoWindowType::ConfirmJump) }
+            if data.jump_index_is_valid() {
+                data.jump_to_jump_index();
+                close_settings_window(ctx, data);
+            }
         });
     let text_jump_to_artist = TextBox::new()
         .with_placeholder(data.jump_index.clone())
@@ -85,22 +224,202 @@ fn create_function_container(data: &AppData) -> impl Widget<AppData> {
         .lens(AppData::jump_index)
         .fix_size((bw / 5) as f64, bh as f64);
 
+    let text_look_up_artist = TextBox::new()
+        .with_placeholder("artist id or pixiv url")
+        .with_font(ui::FONT)
+        .with_text_size((fs-10) as f64)
+        .lens(AppData::lookup_input)
+        .fix_size((bw - (bw / 4)) as f64, bh as f64);
+    let button_look_up_artist = create_button(
+        Label::new("Look up"),
+        bw / 4, bh, fs-10, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            data.look_up_artist();
+            if data.lookup_status.is_empty() { close_settings_window(ctx, data) }
+        });
+    let label_lookup_status = Either::new(
+        |data: &AppData, _| data.lookup_status.is_empty(),
+        SizedBox::empty(),
+        create_button(
+            Label::new(|data: &AppData, _: &_| data.lookup_status.clone()),
+            bw, bh, fs-10, false),
+    );
+
+    let button_mark_reviewed = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Mark all before {} as reviewed", {
+            if data.jump_index_is_valid() { &data.jump_index }
+            else { "" }
+        })}),
+        bw, bh, fs-10, false)
+        .on_click(|ctx, data, _| {
+            if data.jump_index_is_valid() { open_info_window(ctx, data, InfoWindowType::ConfirmMarkReviewed) }
+        });
+    let button_undo_review_marks = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.can_undo_review_marks { "Undo marking as reviewed" }
+            else { "Nothing to undo" }
+        }),
+        bw, bh, fs-10, false)
+        .on_click(|_, data: &mut AppData, _| {
+            if data.can_undo_review_marks { data.undo_review_marks() }
+        });
+
+    let button_browse_artists = create_button(
+        Label::new("Browse the artist list"),
+        bw, bh, fs, false)
+        .on_click(|ctx, data: &mut AppData, _| {
+            open_list_window(ctx, data);
+        });
+
+    let button_queue_sort = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Sort by: {}", describe_queue_sort(&data.settings.queue_sort)) }),
+        bw, bh, fs, false)
+        .on_click(|_, data: &mut AppData, _| {
+            data.cycle_queue_sort();
+        });
+    let button_queue_filter = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.queue_filter_note.is_empty() { format!("Show: {}", describe_queue_filter(&data.settings.queue_filter, &data.settings.activity_months)) }
+            else { format!("Show: {}\n({})", describe_queue_filter(&data.settings.queue_filter, &data.settings.activity_months), data.queue_filter_note) }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data: &mut AppData, _| {
+            data.cycle_queue_filter();
+        });
+
     let button_reload_artist_list = create_button(
         Label::new("Reload the artist list"),
         bw, bh, fs, false)
         .on_click(|ctx, data, _| {
-            if !data.requires_initialization {
+            if data.can_use_pixiv() {
                 open_info_window(ctx, data, InfoWindowType::ConfirmListReload);
             }
         });
 
+    let button_reevaluate_rules = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.rules_report.is_empty() { "Re-check rules for all".to_string() }
+            else { format!("Rules: {}", data.rules_report) }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.reevaluate_rules();
+        });
+
+    let label_scan_status = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.scan_status.is_empty() { "Scan: not started".to_string() }
+            else { data.scan_status.clone() }
+        }),
+        bw, bh, fs-10, false);
+    let button_scan_all = create_button(
+        Label::new("Scan all"),
+        bw/3, bh, fs-10, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            data.start_scan(ctx.get_external_handle(), true);
+        });
+    let button_scan_pause = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.can_resume_scan() { "Resume" }
+            else { "Pause" }
+        }),
+        bw/3, bh, fs-10, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            if data.can_resume_scan() { data.start_scan(ctx.get_external_handle(), false) }
+            else if is_scan_active() { data.pause_scan() }
+        });
+    let button_scan_cancel = create_button(
+        Label::new("Cancel"),
+        bw - (bw/3)*2, bh, fs-10, true)
+        .on_click(|_, data: &mut AppData, _| {
+            data.cancel_scan();
+        });
+
+    let label_bookmark_index = create_button(
+        Label::new(|data: &AppData, _: &_| { format!("Bookmarks: {}", data.bookmark_index_report) }),
+        bw, bh, fs-10, false);
+    let button_sync_bookmarks = create_button(
+        Label::new("Sync new"),
+        bw/2, bh, fs-10, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            data.start_bookmark_sync(ctx.get_external_handle(), false);
+        });
+    let button_resync_bookmarks = create_button(
+        Label::new("Full resync"),
+        bw - bw/2, bh, fs-10, true)
+        .on_click(|ctx, data: &mut AppData, _| {
+            data.start_bookmark_sync(ctx.get_external_handle(), true);
+        });
+
+    let button_offline_mode = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.is_offline { "Go online" }
+            else { "Go offline" }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.toggle_offline_mode();
+        });
+
+    let button_verify_cache = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.cache_report.is_empty() { "Verify the cache".to_string() }
+            else { format!("Verified: {}", data.cache_report) }
+        }),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            data.verify_cache();
+        });
+
+    let label_pending_changes = create_button(
+        Label::new(|data: &AppData, _: &_| {
+            if data.pending_changes.is_empty() { "No pending changes".to_string() }
+            else { format!("Pending changes:\n{}", data.pending_changes) }
+        }).with_line_break_mode(LineBreaking::WordWrap),
+        bw, bh*3, fs-10, false);
+    let button_clear_pending_changes = create_button(
+        Label::new("Clear pending changes"),
+        bw, bh, fs, false)
+        .on_click(|_, data, _| {
+            if !data.pending_changes.is_empty() { data.clear_pending_changes() }
+        });
+
     Flex::column()
         .with_child(
             Flex::row()
                 .with_child(button_jump_to_artist)
                 .with_child(text_jump_to_artist)
         )
+        .with_child(
+            Flex::row()
+                .with_child(text_look_up_artist)
+                .with_child(button_look_up_artist)
+        )
+        .with_child(label_lookup_status)
+        .with_child(button_mark_reviewed)
+        .with_child(button_undo_review_marks)
+        .with_child(button_browse_artists)
+        .with_child(button_queue_sort)
+        .with_child(button_queue_filter)
         .with_child(button_reload_artist_list)
+        .with_child(button_verify_cache)
+        .with_child(button_reevaluate_rules)
+        .with_child(label_bookmark_index)
+        .with_child(
+            Flex::row()
+                .with_child(button_sync_bookmarks)
+                .with_child(button_resync_bookmarks)
+        )
+        .with_child(label_scan_status)
+        .with_child(
+            Flex::row()
+                .with_child(button_scan_all)
+                .with_child(button_scan_pause)
+                .with_child(button_scan_cancel)
+        )
+        .with_child(button_offline_mode)
+        .with_child(label_pending_changes)
+        .with_child(button_clear_pending_changes)
 }
 
 fn create_footer() -> impl Widget<AppData> {
@@ -133,12 +452,19 @@ fn build_ui(data: &AppData) -> impl Widget<AppData> {
     let function_container = create_function_container(data);
     let footer = create_footer();
 
+    // everything between the titlebar and the footer scrolls, so that the window doesn't have to grow with every new setting
+    let scroll_height = ui::SETTINGS_WINDOW_HEIGHT - 40.0 - 40.0 - 10.0 - 10.0;
+    let body = Scroll::new(
+        Flex::column()
+            .with_child(settings_container)
+            .with_spacer(5.0)
+            .with_child(function_container)
+    ).vertical().fix_height(scroll_height);
+
     Flex::column()
         .with_child(titlebar)
         .with_spacer(5.0)
-        .with_child(settings_container)
-        .with_spacer(5.0)
-        .with_child(function_container)
+        .with_child(body)
         .with_spacer(5.0)
         .with_child(footer)
         .padding(5.0)
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Duration, Utc};
+use crate::data::{ArtistSummary, Illustration, UserData};
+
+/// Returns the time that was the amount of 30 day months ago
+pub fn months_ago(months: i64) -> DateTime<Utc> {
+    Utc::now() - Duration::days(months * 30)
+}
+
+/// Followed artist who last uploaded and got bookmarked a month ago
+pub fn make_summary() -> ArtistSummary {
+    ArtistSummary {
+        name: "Artist".to_string(),
+        recent_count: 5,
+        recent_months: 6,
+        is_followed: true,
+        last_bookmarked: Some(months_ago(1)),
+        latest_upload: Some(months_ago(1)),
+        works_since_last_bookmark: Some(10),
+        searched_count: 100,
+        bookmarked_count: 5,
+        shown_count: 4,
+        shown_bookmarked: 1,
+    }
+}
+
+/// All ages illustration without tags
+pub fn make_illust() -> Illustration {
+    Illustration {
+        id: 1,
+        views: 0,
+        bookmarks: 0,
+        upload_date: None,
+        is_bookmarked: false,
+        tags: Vec::new(),
+        x_restrict: Some(0),
+        is_ai: false,
+    }
+}
+
+/// Default settings with the content filters set
+pub fn make_filter_settings(x_restrict_filter: &str, ai_filter: &str, tag_blacklist: &str) -> UserData {
+    UserData {
+        x_restrict_filter: x_restrict_filter.to_string(),
+        ai_filter: ai_filter.to_string(),
+        tag_blacklist: tag_blacklist.to_string(),
+        ..UserData::load_default()
+    }
+}
@@ -4,17 +4,26 @@ pub mod ui {
     use crate::data::AppData;
 
     pub const WINDOW_WIDTH: f64 = 945.0;
-    pub const WINDOW_HEIGHT: f64 = 545.0;
+    pub const WINDOW_HEIGHT: f64 = 645.0;
     pub const SETTINGS_WINDOW_WIDTH: f64 = 400.0;
-    pub const SETTINGS_WINDOW_HEIGHT: f64 = 425.0;
+    pub const SETTINGS_WINDOW_HEIGHT: f64 = 465.0;
     pub const INFO_WINDOW_WIDTH: f64 = 300.0;
     pub const INFO_WINDOW_HEIGHT: f64 = 195.0;
+    pub const RANKING_WINDOW_WIDTH: f64 = 400.0;
+    pub const RANKING_WINDOW_HEIGHT: f64 = 465.0;
+    pub const LIST_WINDOW_WIDTH: f64 = 780.0;
+    pub const LIST_WINDOW_HEIGHT: f64 = 580.0;
+    pub const BOOKMARK_WINDOW_WIDTH: f64 = 400.0;
+    pub const BOOKMARK_WINDOW_HEIGHT: f64 = 465.0;
     
     pub static mut SCREEN_WIDTH: f64 = 0.0;
     pub static mut SCREEN_HEIGHT: f64 = 0.0;
     pub static mut WINDOW_POS: Point = Point { x: 0.0, y: 0.0 };
     pub static mut SETTINGS_WINDOW_POS: Point = Point { x: 0.0, y: 0.0 };
     pub static mut INFO_WINDOW_POS: Point = Point { x: 0.0, y: 0.0 };
+    pub static mut RANKING_WINDOW_POS: Point = Point { x: 0.0, y: 0.0 };
+    pub static mut LIST_WINDOW_POS: Point = Point { x: 0.0, y: 0.0 };
+    pub static mut BOOKMARK_WINDOW_POS: Point = Point { x: 0.0, y: 0.0 };
 
     pub const FONT: FontDescriptor = FontDescriptor::new(FontFamily::MONOSPACE);
     pub const INACTIVE_COLOR: Color = Color::rgb8(0, 120, 200);
@@ -23,12 +32,31 @@ pub mod ui {
 
     pub const DEFAULT_AMOUNT_TO_SEARCH: &str = "210";
     pub const DEFAULT_TIMEZONE: &str = "Etc/GMT-9";
+    pub const DEFAULT_ACTIVITY_MONTHS: &str = "6";
+    pub const DEFAULT_SCORE_WEIGHTS: &str = "50,30,20";
+    pub const DEFAULT_DATE_FORMAT: &str = "absolute";
+    pub const DEFAULT_MAX_CACHE_SIZE: &str = "200";
+    pub const DEFAULT_MAX_CACHED_ARTISTS: &str = "30";
+    pub const DEFAULT_FOLLOW_LIST: &str = "public";
+    pub const DEFAULT_ACTIVITY_WORKS: &str = "all";
+    pub const DEFAULT_X_RESTRICT_FILTER: &str = "none";
+    pub const DEFAULT_AI_FILTER: &str = "show";
+    pub const DEFAULT_FILTER_MODE: &str = "blur";
+    pub const DEFAULT_CARD_MODE: &str = "recent";
+    pub const DEFAULT_QUEUE_SORT: &str = "follow_date";
+    pub const DEFAULT_QUEUE_FILTER: &str = "all";
 
     // This allows closing a window by clicking on a button in foreign windows. Ideally I would store this in the AppData, but druid doesn't allow that
     #[allow(non_upper_case_globals)]
     pub static mut setting_window_id: Option<WindowId> = None;
     #[allow(non_upper_case_globals)]
     pub static mut info_window_id: Option<WindowId> = None;
+    #[allow(non_upper_case_globals)]
+    pub static mut ranking_window_id: Option<WindowId> = None;
+    #[allow(non_upper_case_globals)]
+    pub static mut list_window_id: Option<WindowId> = None;
+    #[allow(non_upper_case_globals)]
+    pub static mut bookmark_window_id: Option<WindowId> = None;
 
     /// Pre-initialize positions for all windows that the app could open. If center_window is true, the main window will appear in the center of the screen. Otherwise, it will appear in the top right corner. TODO: make SCREEN_WIDTH and SCREEN_HEIGHT initialize based on an actual screen size
     pub fn initialize_window_positions(center_window: bool) {
@@ -55,6 +83,21 @@ pub mod ui {
                 y: WINDOW_POS.y + (WINDOW_HEIGHT / 2.0) - (SETTINGS_WINDOW_HEIGHT / 2.0),
             };
 
+            RANKING_WINDOW_POS = Point {
+                x: WINDOW_POS.x + (WINDOW_WIDTH / 2.0) - (RANKING_WINDOW_WIDTH / 2.0),
+                y: WINDOW_POS.y + (WINDOW_HEIGHT / 2.0) - (RANKING_WINDOW_HEIGHT / 2.0),
+            };
+
+            LIST_WINDOW_POS = Point {
+                x: WINDOW_POS.x + (WINDOW_WIDTH / 2.0) - (LIST_WINDOW_WIDTH / 2.0),
+                y: WINDOW_POS.y + (WINDOW_HEIGHT / 2.0) - (LIST_WINDOW_HEIGHT / 2.0),
+            };
+
+            BOOKMARK_WINDOW_POS = Point {
+                x: WINDOW_POS.x + (WINDOW_WIDTH / 2.0) - (BOOKMARK_WINDOW_WIDTH / 2.0),
+                y: WINDOW_POS.y + (WINDOW_HEIGHT / 2.0) - (BOOKMARK_WINDOW_HEIGHT / 2.0),
+            };
+
             INFO_WINDOW_POS = Point {
                 x: SETTINGS_WINDOW_POS.x + (SETTINGS_WINDOW_WIDTH / 2.0) - (INFO_WINDOW_WIDTH / 2.0),
                 y: SETTINGS_WINDOW_POS.y + (SETTINGS_WINDOW_HEIGHT / 2.0) - (INFO_WINDOW_HEIGHT / 2.0),
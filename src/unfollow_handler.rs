@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Artists that were unfollowed through PAR, together with why. Kept separately from artists.json, so that reloading the artist list doesn't lose them
+const UNFOLLOWED_PATH: &str = "./unfollowed.json";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UnfollowRecord {
+    pub artist_id: u32,
+    pub name: String,
+    pub reason: String,
+    pub muted: bool,
+    pub removed_bookmarks: bool,
+    pub unfollowed_at: DateTime<Utc>,
+}
+
+/// Reads unfollowed.json. Returns no records if the file is missing or invalid
+pub fn load_unfollow_records() -> Vec<UnfollowRecord> {
+    std::fs::read_to_string(UNFOLLOWED_PATH).ok()
+        .and_then(|file_cont| serde_json::from_str(&file_cont).ok())
+        .unwrap_or_default()
+}
+
+/// Adds the record to unfollowed.json, replacing the older record of the same artist
+pub fn record_unfollow(record: UnfollowRecord) {
+    let mut records = load_unfollow_records();
+    records.retain(|r| r.artist_id != record.artist_id);
+    records.push(record);
+
+    let file_cont = serde_json::to_string_pretty(&records)
+        .expect("Could not convert data to json");
+    std::fs::write(UNFOLLOWED_PATH, file_cont)
+        .expect("Could not write to unfollowed.json");
+}